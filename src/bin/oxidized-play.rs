@@ -0,0 +1,153 @@
+//! Plays the games of the bot in the terminal, without needing a discord token.
+//!
+//! Usage: `oxidized-play <game> [--ai | --ai-first] [--ascii]`
+
+use oxidized::game::{render::*, *};
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    process,
+};
+
+enum Player<T, G: PvpGame<T>> {
+    Person,
+    Ai(Box<dyn AiPlayer<T, G> + Send + Sync>),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Opponent {
+    Person,
+    /// the AI plays second
+    Ai,
+    /// the AI plays first
+    AiFirst,
+}
+
+macro_rules! games {
+    ($($name:literal => $game:expr,)*) => {
+        const GAMES: &[&str] = &[$($name),*];
+
+        fn start(name: &str, opponent: Opponent, style: Style) -> Result<(), String> {
+            match name {
+                $($name => play($game, opponent, style),)*
+                _ => Err(format!("There is no game called `{}`.", name)),
+            }
+        }
+    };
+}
+
+games! {
+    "tictactoe" => tictactoe::TTTField::default(),
+    "ultimate" => ultimate::UltimateGame::new(),
+    "connect4" => connect4::Connect4::default(),
+    "pentago" => pentago::Pentago::default(),
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: oxidized-play <game> [--ai | --ai-first] [--ascii]");
+    eprintln!();
+    eprintln!("  --ai        play against the AI, you make the first move");
+    eprintln!("  --ai-first  play against the AI, the AI makes the first move");
+    eprintln!("  --ascii     draw the board with plain ASCII instead of emojis");
+    eprintln!();
+    eprintln!("Available games: {}", GAMES.join(", "));
+    process::exit(1);
+}
+
+fn main() {
+    let mut game = None;
+    let mut opponent = Opponent::Person;
+    let mut style = Style::Emoji;
+
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--ai" => opponent = Opponent::Ai,
+            "--ai-first" => opponent = Opponent::AiFirst,
+            "--ascii" => style = Style::Ascii,
+            _ if arg.starts_with('-') || game.is_some() => usage(),
+            _ => game = Some(arg),
+        }
+    }
+
+    let game = game.unwrap_or_else(|| usage());
+
+    if let Err(err) = start(&game, opponent, style) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn play<T, G: PvpGame<T>>(mut game: G, opponent: Opponent, style: Style) -> Result<(), String> {
+    let mut players = vec![Player::Person, Player::Person];
+    if opponent != Opponent::Person {
+        let ai = G::ai().ok_or(format!("{} doesn't support AI players.", G::title()))?;
+        let idx = if opponent == Opponent::AiFirst { 0 } else { 1 };
+        players[idx] = Player::Ai(ai);
+    }
+
+    let renderer = style.renderer();
+    let figures = (0..2)
+        .map(|p| renderer.piece(&G::pieces(), p))
+        .collect::<Vec<_>>();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut turn = 0;
+
+    println!("{}", G::title());
+
+    let state = loop {
+        println!("\n{}\n", game.draw(renderer));
+
+        let play = match &mut players[turn] {
+            Player::Person => {
+                print!("Player {} ({}), your move: ", turn + 1, figures[turn]);
+                io::stdout().flush().map_err(|e| e.to_string())?;
+
+                let line = match lines.next() {
+                    Some(line) => line.map_err(|e| e.to_string())?,
+                    None => return Ok(()),
+                };
+                match G::parse_move(&line) {
+                    Some(play) => play,
+                    None => {
+                        println!("Couldn't understand `{}`.", line.trim());
+                        continue;
+                    }
+                }
+            }
+            Player::Ai(ai) => ai.make_move(&game, turn),
+        };
+
+        let description = game.describe_move(&play);
+        match game.make_move(play, turn) {
+            GameState::Invalid if players[turn].is_ai() => {
+                return Err(String::from("The AI tried to make an invalid move."));
+            }
+            GameState::Invalid => println!("This move is not allowed."),
+            state => {
+                println!("Player {} {}.", turn + 1, description);
+                if state.is_finished() {
+                    break state;
+                }
+                turn = 1 - turn;
+            }
+        }
+    };
+
+    println!("\n{}\n", game.draw(renderer));
+    match state {
+        GameState::Win(p) => println!("Player {} ({}) won!", p + 1, figures[p]),
+        _ => println!("It's a tie!"),
+    }
+
+    Ok(())
+}
+
+impl<T, G: PvpGame<T>> Player<T, G> {
+    fn is_ai(&self) -> bool {
+        match self {
+            Self::Ai(_) => true,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,1014 @@
+//! Discord independent brainfuck interpreter.
+//!
+//! Programs are compiled into a compact intermediate representation first: runs of `+-<>` are
+//! folded and common loops like clearing a cell, moving or multiplying a cell into others and
+//! scanning for a zero cell become single instructions. Dialects without wrapping only fold what
+//! can't hide an error, like `-+` on a zero cell.
+
+use crate::esolang::*;
+use std::collections::*;
+use std::sync::atomic::AtomicBool;
+
+/// Largest tape an infinite tape can grow to. Fixed tapes also only grow up to their length
+/// when their cells are used, so piped programs don't allocate tapes they never touch.
+const MAX_TAPE: usize = 1 << 20;
+
+/// How big the cells of the tape are
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum CellSize {
+    U8,
+    U16,
+    U32,
+}
+
+impl CellSize {
+    fn max(self) -> i64 {
+        match self {
+            Self::U8 => u8::MAX.into(),
+            Self::U16 => u16::MAX.into(),
+            Self::U32 => u32::MAX.into(),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Tape {
+    /// a tape with a fixed number of cells
+    Fixed(usize),
+    /// a tape that grows to the right as it is used
+    Infinite,
+}
+
+/// What `,` does after the end of the input
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Eof {
+    Zero,
+    MinusOne,
+    Unchanged,
+}
+
+/// The variant of brainfuck a program is run with
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Dialect {
+    pub cells: CellSize,
+    pub tape: Tape,
+    pub eof: Eof,
+    /// whether cells and the pointer on a fixed tape wrap around instead of causing an error
+    pub wrap: bool,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self {
+            cells: CellSize::U8,
+            tape: Tape::Fixed(30_000),
+            eof: Eof::Zero,
+            wrap: true,
+        }
+    }
+}
+
+impl Dialect {
+    /// Changes the dialect by a flag like `--cells=u16`, `--tape=infinite`, `--eof=-1` or
+    /// `--no-wrap`
+    pub fn apply_flag(&mut self, flag: &str) -> Result<(), &'static str> {
+        let flag = flag.trim_start_matches("--");
+        let (name, value) = match flag.find('=') {
+            Some(idx) => (&flag[..idx], &flag[idx + 1..]),
+            None => (flag, ""),
+        };
+        match (name, value) {
+            ("cells", "u8") => self.cells = CellSize::U8,
+            ("cells", "u16") => self.cells = CellSize::U16,
+            ("cells", "u32") => self.cells = CellSize::U32,
+            ("cells", _) => return Err("cells are `u8`, `u16` or `u32`"),
+            ("tape", "infinite") => self.tape = Tape::Infinite,
+            ("tape", len) => match len.parse::<usize>() {
+                Ok(len) if len > 0 && len <= MAX_TAPE => self.tape = Tape::Fixed(len),
+                _ => return Err("the tape is `infinite` or a number of cells up to 1048576"),
+            },
+            ("eof", "0") => self.eof = Eof::Zero,
+            ("eof", "-1") => self.eof = Eof::MinusOne,
+            ("eof", "unchanged") => self.eof = Eof::Unchanged,
+            ("eof", _) => return Err("the end of the input is `0`, `-1` or `unchanged`"),
+            ("wrap", "") => self.wrap = true,
+            ("no-wrap", "") => self.wrap = false,
+            _ => return Err("unknown flag"),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Instr {
+    /// adds a number to the current cell
+    Add(i32),
+    /// moves the pointer by some cells
+    Move(isize),
+    Output,
+    Input,
+    JumpRight(usize),
+    JumpLeft(usize),
+    /// `[-]`, sets the current cell to zero
+    Clear,
+    /// part of a loop like `[->++<]`, adds the current cell times a factor to another cell
+    MulAdd {
+        offset: isize,
+        factor: i32,
+    },
+    /// `[>]`, moves the pointer in steps until it reaches a zero cell
+    Scan(isize),
+    /// `#`, only used by the debugger
+    Breakpoint,
+    Terminate,
+}
+
+/// Compiles a brainfuck program for the default dialect, characters that aren't instructions are
+/// ignored
+pub fn parse_instructions(string: &str) -> Result<Vec<Instr>, ParseError> {
+    parse_dialect(string, Dialect::default())
+}
+
+/// Compiles a brainfuck program for a dialect, which decides which instructions can be folded
+pub fn parse_dialect(string: &str, dialect: Dialect) -> Result<Vec<Instr>, ParseError> {
+    let (positions, source): (Vec<_>, Vec<_>) = string
+        .chars()
+        .enumerate()
+        .filter(|&(_, ch)| "<>+-.,[]".contains(ch))
+        .map(|(pos, ch)| (pos, ch as u8))
+        .unzip();
+
+    let folding = Folding::of(dialect);
+    let mut program = Vec::new();
+    let mut stack = VecDeque::new();
+    let mut idx = 0;
+    while idx < source.len() {
+        let ch = source[idx];
+        match ch {
+            b'+' | b'-' => {
+                let (len, value) = fold(&source[idx..], b'+', b'-', folding.cells);
+                idx += len;
+                if value != 0 {
+                    program.push(Instr::Add(value as i32));
+                }
+                continue;
+            }
+            b'>' | b'<' => {
+                let (len, value) = fold(&source[idx..], b'>', b'<', folding.pointer);
+                idx += len;
+                if value != 0 {
+                    program.push(Instr::Move(value));
+                }
+                continue;
+            }
+            b'.' => program.push(Instr::Output),
+            b',' => program.push(Instr::Input),
+            b'[' => {
+                if let Some((len, instrs)) = simple_loop(&source[idx..], folding) {
+                    program.extend(instrs);
+                    idx += len;
+                    continue;
+                }
+                stack.push_back((program.len(), positions[idx]));
+                program.push(Instr::JumpRight(0));
+            }
+            b']' => close_loop(&mut program, &mut stack, positions[idx])?,
+            _ => unreachable!(),
+        }
+        idx += 1;
+    }
+    unclosed_loop(&stack)?;
+    program.push(Instr::Terminate);
+    Ok(program)
+}
+
+/// Compiles a program without optimizations, so every instruction belongs to one character.
+/// `#` becomes a breakpoint. Also returns the position of every instruction in the program.
+pub fn parse_debug(string: &str) -> Result<(Vec<Instr>, Vec<usize>), ParseError> {
+    let mut program = Vec::new();
+    let mut positions = Vec::new();
+    let mut stack = VecDeque::new();
+    for (pos, ch) in string.chars().enumerate() {
+        match ch {
+            '>' => program.push(Instr::Move(1)),
+            '<' => program.push(Instr::Move(-1)),
+            '+' => program.push(Instr::Add(1)),
+            '-' => program.push(Instr::Add(-1)),
+            '.' => program.push(Instr::Output),
+            ',' => program.push(Instr::Input),
+            '#' => program.push(Instr::Breakpoint),
+            '[' => {
+                stack.push_back((program.len(), pos));
+                program.push(Instr::JumpRight(0));
+            }
+            ']' => close_loop(&mut program, &mut stack, pos)?,
+            _ => continue,
+        }
+        positions.push(pos);
+    }
+    unclosed_loop(&stack)?;
+    program.push(Instr::Terminate);
+    positions.push(string.chars().count());
+    Ok((program, positions))
+}
+
+/// Links the jump at the start of the innermost open loop with its end at a position
+fn close_loop(
+    program: &mut Vec<Instr>,
+    stack: &mut VecDeque<(usize, usize)>,
+    position: usize,
+) -> Result<(), ParseError> {
+    let target = match stack.pop_back() {
+        Some((t, _)) => t,
+        None => {
+            return Err(ParseError {
+                message: "`]` without a matching `[`",
+                position,
+            })
+        }
+    };
+    let idx = program.len();
+    match program[target] {
+        Instr::JumpRight(ref mut target) => *target = idx + 1,
+        _ => unreachable!(),
+    };
+    program.push(Instr::JumpLeft(target + 1));
+    Ok(())
+}
+
+/// Fails if a loop is still open at the end of the program
+fn unclosed_loop(stack: &VecDeque<(usize, usize)>) -> Result<(), ParseError> {
+    match stack.back() {
+        Some(&(_, position)) => Err(ParseError {
+            message: "`[` without a matching `]`",
+            position,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Which runs of opposite instructions can be folded without hiding an error
+#[derive(Copy, Clone)]
+struct Folding {
+    /// whether `+-` can be folded, only if cells wrap around
+    cells: bool,
+    /// whether `<>` can be folded, only if the pointer wraps around
+    pointer: bool,
+}
+
+impl Folding {
+    fn of(dialect: Dialect) -> Self {
+        Self {
+            cells: dialect.wrap,
+            pointer: dialect.wrap && dialect.tape != Tape::Infinite,
+        }
+    }
+}
+
+/// Length and sum of a run of two opposite instructions.
+/// Without `mixed`, the run only contains the instruction it starts with.
+fn fold(source: &[u8], up: u8, down: u8, mixed: bool) -> (usize, isize) {
+    let len = source
+        .iter()
+        .take_while(|&&ch| {
+            if mixed {
+                ch == up || ch == down
+            } else {
+                ch == source[0]
+            }
+        })
+        .count();
+    let value = source[..len]
+        .iter()
+        .map(|&ch| if ch == up { 1 } else { -1 })
+        .sum::<isize>();
+    (len, value)
+}
+
+/// Replaces a loop at the start of the source with faster instructions, if it only clears,
+/// moves or multiplies cells or scans for a zero cell, and the new instructions fail where the
+/// loop would fail.
+/// Returns the length of the loop in the source and the new instructions.
+fn simple_loop(source: &[u8], folding: Folding) -> Option<(usize, Vec<Instr>)> {
+    let len = source.iter().skip(1).position(|&ch| ch == b']')? + 2;
+    let body = &source[1..len - 1];
+    if body.iter().any(|ch| !b"<>+-".contains(ch)) {
+        return None;
+    }
+
+    // a loop that only moves scans for a zero cell
+    if body.iter().all(|&ch| ch == b'<' || ch == b'>') {
+        if !folding.pointer && body.iter().any(|&ch| ch != body[0]) {
+            return None;
+        }
+        let (_, step) = fold(body, b'>', b'<', true);
+        return match step {
+            0 => None,
+            step => Some((len, vec![Instr::Scan(step)])),
+        };
+    }
+
+    // changes of the cells relative to the pointer at the start of the loop
+    let mut changes = BTreeMap::<isize, i32>::new();
+    // cells that are counted up and down in the same loop
+    let mut mixed = BTreeSet::new();
+    let mut offset = 0;
+    let (mut lowest, mut highest) = (0, 0);
+    for &ch in body {
+        match ch {
+            b'>' => offset += 1,
+            b'<' => offset -= 1,
+            _ => {
+                let change = if ch == b'+' { 1 } else { -1 };
+                let factor = changes.entry(offset).or_insert(0);
+                if *factor != 0 && (*factor > 0) != (change > 0) {
+                    mixed.insert(offset);
+                }
+                *factor += change;
+            }
+        }
+        lowest = lowest.min(offset);
+        highest = highest.max(offset);
+    }
+
+    // the loop has to end where it started and count the current cell down by one
+    if offset != 0 || changes.remove(&0) != Some(-1) {
+        return None;
+    }
+    let changes = changes
+        .into_iter()
+        .filter(|&(_, factor)| factor != 0)
+        .collect::<BTreeMap<_, _>>();
+    // a cell can only overflow in between if it is counted up and down
+    if !folding.cells && !mixed.is_empty() {
+        return None;
+    }
+    // the pointer can only leave the tape in between if the outermost cells aren't changed
+    let reaches = |end: isize| end == 0 || changes.contains_key(&end);
+    let checked = reaches(lowest) && reaches(highest);
+    if !folding.pointer && !checked {
+        return None;
+    }
+    let mut instrs = changes
+        .into_iter()
+        .map(|(offset, factor)| Instr::MulAdd { offset, factor })
+        .collect::<Vec<_>>();
+    instrs.push(Instr::Clear);
+    Some((len, instrs))
+}
+
+pub struct ProgContext {
+    code: Vec<Instr>,
+    data: Vec<u32>,
+    ptr: usize,
+    ip: usize,
+    dialect: Dialect,
+    /// the highest cell that was read or written
+    max_cell: usize,
+    /// steps that the last instruction took in addition to the first one
+    extra_steps: usize,
+    /// how many extra steps the next instruction may take
+    extra_limit: usize,
+}
+
+impl ProgContext {
+    pub fn new(code: Vec<Instr>) -> Self {
+        Self::with_dialect(code, Dialect::default())
+    }
+
+    pub fn with_dialect(code: Vec<Instr>, dialect: Dialect) -> Self {
+        Self {
+            code,
+            data: vec![0; 1],
+            ptr: 0,
+            ip: 0,
+            dialect,
+            max_cell: 0,
+            extra_steps: 0,
+            extra_limit: usize::MAX,
+        }
+    }
+
+    /// how many cells the tape can have
+    fn tape_len(&self) -> usize {
+        match self.dialect.tape {
+            Tape::Fixed(len) => len,
+            Tape::Infinite => MAX_TAPE,
+        }
+    }
+
+    /// whether the pointer wraps around at the ends of the tape
+    fn wraps(&self) -> bool {
+        self.dialect.wrap && self.dialect.tape != Tape::Infinite
+    }
+
+    /// the cell that is some cells away from the pointer, the tape grows to contain it
+    fn cell(&mut self, offset: isize) -> Result<usize, ExitCode> {
+        let pos = self.ptr as isize + offset;
+        let len = self.tape_len() as isize;
+        let cell = if self.wraps() {
+            pos.rem_euclid(len) as usize
+        } else if pos < 0 || pos >= len {
+            return Err(ExitCode::PointerOutOfBounds);
+        } else {
+            pos as usize
+        };
+        if cell >= self.data.len() {
+            self.data.resize(cell + 1, 0);
+        }
+        Ok(cell)
+    }
+
+    /// remembers that a cell was read or written
+    fn touch(&mut self, cell: usize) {
+        self.max_cell = self.max_cell.max(cell);
+    }
+
+    /// adds a number to a cell, wrapping around or failing if it leaves the range of the cells
+    fn add(&mut self, cell: usize, change: i64) -> Result<(), ExitCode> {
+        let max = self.dialect.cells.max();
+        let value = i64::from(self.data[cell]) + change;
+        self.data[cell] = if self.dialect.wrap {
+            value.rem_euclid(max + 1) as u32
+        } else if (0..=max).contains(&value) {
+            value as u32
+        } else {
+            return Err(ExitCode::CellOverflow);
+        };
+        Ok(())
+    }
+
+    fn execute(&mut self, input: &mut Option<Input>) -> Result<Option<Output>, ExitCode> {
+        // only moving the pointer doesn't use the current cell
+        if !matches!(
+            self.code[self.ip],
+            Instr::Move(_) | Instr::Breakpoint | Instr::Terminate
+        ) {
+            self.touch(self.ptr);
+        }
+        match self.code[self.ip] {
+            Instr::Add(value) => self.add(self.ptr, value.into())?,
+            Instr::Move(offset) => self.ptr = self.cell(offset)?,
+            Instr::Input => match input.take() {
+                None => return Ok(Some(Output::Starved)),
+                Some(Input::Byte(val)) => self.data[self.ptr] = val.into(),
+                Some(Input::Eof) => match self.dialect.eof {
+                    Eof::Zero => self.data[self.ptr] = 0,
+                    Eof::MinusOne => self.data[self.ptr] = self.dialect.cells.max() as u32,
+                    Eof::Unchanged => {}
+                },
+            },
+            Instr::Output => {
+                self.ip += 1;
+                return Ok(Some(Output::Value(self.data[self.ptr] as u8)));
+            }
+            Instr::JumpRight(target) => {
+                if self.data[self.ptr] == 0 {
+                    self.ip = target;
+                    return Ok(None);
+                }
+            }
+            Instr::JumpLeft(target) => {
+                if self.data[self.ptr] > 0 {
+                    self.ip = target;
+                    return Ok(None);
+                }
+            }
+            Instr::Clear => self.data[self.ptr] = 0,
+            Instr::MulAdd { offset, factor } => {
+                // the loop it replaces doesn't run on a zero cell, so nothing is touched
+                if self.data[self.ptr] != 0 {
+                    let change = i64::from(self.data[self.ptr]) * i64::from(factor);
+                    let cell = self.cell(offset)?;
+                    self.touch(cell);
+                    self.add(cell, change)?;
+                }
+            }
+            Instr::Scan(step) => {
+                for _ in 0..self.extra_limit.min(MAX_TAPE) {
+                    if self.data[self.ptr] == 0 {
+                        break;
+                    }
+                    self.ptr = self.cell(step)?;
+                    self.touch(self.ptr);
+                    // every cell that is passed counts, like the loop it replaces
+                    self.extra_steps += 1;
+                }
+                // there might be no zero cell or the budget might be used up, so the clock and
+                // the budget have to be checked again
+                if self.data[self.ptr] != 0 {
+                    return Ok(None);
+                }
+            }
+            Instr::Breakpoint => {}
+            Instr::Terminate => return Ok(Some(Output::Terminated)),
+        }
+        self.ip += 1;
+        Ok(None)
+    }
+
+    /// index of the next instruction
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn instruction(&self) -> Instr {
+        self.code[self.ip]
+    }
+
+    pub fn ptr(&self) -> usize {
+        self.ptr
+    }
+
+    /// the cells around the pointer that exist, with their index
+    pub fn tape_window(&self, radius: usize) -> Vec<(usize, u32)> {
+        let radius = radius as isize;
+        let len = self.tape_len() as isize;
+        (-radius..=radius)
+            .map(|offset| self.ptr as isize + offset)
+            .filter_map(|pos| match pos {
+                _ if self.wraps() => Some(pos.rem_euclid(len)),
+                pos if pos >= 0 && pos < len => Some(pos),
+                _ => None,
+            })
+            .map(|cell| {
+                let cell = cell as usize;
+                (cell, self.data.get(cell).copied().unwrap_or(0))
+            })
+            .collect()
+    }
+
+    /// Runs programs where each one reads the output of the one before, the first one reads
+    /// the input of the user. The output of the last program is cut off after some bytes.
+    pub fn execute_piped(
+        progs: &mut [Self],
+        user_input: &[u8],
+        time_limit: f64,
+        char_limit: usize,
+    ) -> Execution {
+        let cancel = AtomicBool::new(false);
+        execute(
+            progs,
+            user_input,
+            Limit::Time(time_limit),
+            char_limit,
+            &cancel,
+        )
+    }
+}
+
+impl Interpreter for ProgContext {
+    fn step(&mut self, input: &mut Option<Input>) -> Option<Output> {
+        match self.execute(input) {
+            Ok(output) => output,
+            Err(exit_code) => Some(Output::Error(exit_code)),
+        }
+    }
+
+    fn take_extra_steps(&mut self) -> usize {
+        std::mem::take(&mut self.extra_steps)
+    }
+
+    fn limit_extra_steps(&mut self, steps: usize) {
+        self.extra_limit = steps;
+    }
+
+    fn max_cell(&self) -> Option<usize> {
+        Some(self.max_cell)
+    }
+}
+
+/// An input of a challenge and the output a program has to write for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+}
+
+/// How a program did on the test cases of a challenge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// every case passed, with the steps of all cases together
+    Passed { steps: usize },
+    /// index of the first case that failed and how it ended
+    Failed { case: usize, execution: Execution },
+}
+
+/// Runs a program on every test case, each case has its own limit.
+/// Stops with `ExitCode::Cancelled` once the flag is set.
+pub fn judge(program: &[Instr], cases: &[TestCase], limit: Limit, cancel: &AtomicBool) -> Verdict {
+    let mut steps = 0;
+    for (case, test) in cases.iter().enumerate() {
+        let mut progs = [ProgContext::new(program.to_vec())];
+        // one byte more than expected, so a longer output doesn't pass
+        let execution = execute(
+            &mut progs,
+            &test.input,
+            limit,
+            test.output.len() + 1,
+            cancel,
+        );
+        if execution.exit_code != ExitCode::Success || execution.output != test.output {
+            return Verdict::Failed { case, execution };
+        }
+        steps += execution.steps;
+    }
+    Verdict::Passed { steps }
+}
+
+/// Replaces the escape sequences `\n`, `\t`, `\0`, `\\` and `\xHH` with the bytes they stand for
+pub fn unescape(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut chars = text.bytes();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some(b'n') => b'\n',
+            Some(b't') => b'\t',
+            Some(b'0') => 0,
+            Some(b'\\') => b'\\',
+            Some(b'x') => {
+                let hex = [chars.next(), chars.next()];
+                let hex = match hex {
+                    [Some(high), Some(low)] => String::from_utf8(vec![high, low]).ok(),
+                    _ => None,
+                };
+                hex.and_then(|hex| u8::from_str_radix(&hex, 16).ok())
+                    .ok_or("`\\x` needs two hex digits")?
+            }
+            _ => Err("unknown escape sequence")?,
+        };
+        bytes.push(escaped);
+    }
+    Ok(bytes)
+}
+
+/// A stored program in an archive of programs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Archived {
+    pub name: String,
+    pub language: Language,
+    pub public: bool,
+    pub description: String,
+    pub program: String,
+}
+
+/// Writes programs into an archive. Every program starts with a `!program <name>` line,
+/// which ends in ` public` for public programs and in the language if it isn't brainfuck,
+/// and an optional `!description` line, with line breaks and backslashes escaped the way
+/// `unescape` reads them. The code follows with a line break, its lines that start with `!`
+/// get another `!` in front, so they aren't taken for these lines.
+pub fn to_archive(programs: &[Archived]) -> String {
+    let mut archive = String::new();
+    for program in programs {
+        archive += &format!("!program {}", program.name);
+        if program.public {
+            archive += " public";
+        }
+        if program.language != Language::Brainfuck {
+            archive += " ";
+            archive += program.language.name();
+        }
+        archive += "\n";
+        if !program.description.is_empty() {
+            let description = program
+                .description
+                .replace('\\', "\\\\")
+                .replace('\n', "\\n")
+                .replace('\r', "\\x0d");
+            archive += &format!("!description {}\n", description);
+        }
+        for line in program.program.split('\n') {
+            if line.starts_with('!') {
+                archive += "!";
+            }
+            archive += line;
+            archive += "\n";
+        }
+    }
+    archive
+}
+
+/// Reads the programs of an archive written by `to_archive`
+pub fn from_archive(archive: &str) -> Result<Vec<Archived>, String> {
+    let mut programs: Vec<Archived> = Vec::new();
+    for (idx, line) in archive.lines().enumerate() {
+        if let Some(code) = line.strip_prefix("!!") {
+            let program = programs
+                .last_mut()
+                .ok_or(format!("line {}: expected `!program <name>`", idx + 1))?;
+            program.program += "!";
+            program.program += code;
+            program.program.push('\n');
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("!program ") {
+            let mut words = header.split_whitespace();
+            let name = words
+                .next()
+                .ok_or(format!("line {}: missing name", idx + 1))?;
+            let mut program = Archived {
+                name: name.to_string(),
+                language: Language::Brainfuck,
+                public: false,
+                description: String::new(),
+                program: String::new(),
+            };
+            for word in words {
+                match Language::from_name(word) {
+                    _ if word == "public" => program.public = true,
+                    Some(language) => program.language = language,
+                    None => return Err(format!("line {}: unknown word `{}`", idx + 1, word)),
+                }
+            }
+            programs.push(program);
+            continue;
+        }
+        let program = programs
+            .last_mut()
+            .ok_or(format!("line {}: expected `!program <name>`", idx + 1))?;
+        match line.strip_prefix("!description ") {
+            Some(description) if program.program.is_empty() => {
+                let description = unescape(description.trim())
+                    .map_err(|why| format!("line {}: {}", idx + 1, why))?;
+                program.description = String::from_utf8(description)
+                    .map_err(|_| format!("line {}: invalid description", idx + 1))?;
+            }
+            _ => {
+                program.program += line;
+                program.program.push('\n');
+            }
+        }
+    }
+    // the line break after the code isn't part of the program
+    for program in programs.iter_mut() {
+        if program.program.ends_with('\n') {
+            program.program.pop();
+        }
+    }
+    Ok(programs)
+}
+
+#[cfg(test)]
+fn run(programs: &[&str], input: &str) -> (Vec<u8>, ExitCode) {
+    run_dialect(programs, input, Dialect::default())
+}
+
+#[cfg(test)]
+fn run_dialect(programs: &[&str], input: &str, dialect: Dialect) -> (Vec<u8>, ExitCode) {
+    let mut progs = programs
+        .iter()
+        .map(|program| ProgContext::with_dialect(parse_dialect(program, dialect).unwrap(), dialect))
+        .collect::<Vec<_>>();
+    let execution = ProgContext::execute_piped(&mut progs, input.as_bytes(), 1.0, 1000);
+    (execution.output, execution.exit_code)
+}
+
+#[test]
+fn optimization_test() {
+    use Instr::*;
+    assert_eq!(
+        parse_instructions("+++-->><<<[-]").unwrap(),
+        vec![Add(1), Move(-1), Clear, Terminate]
+    );
+    assert_eq!(
+        parse_instructions("[->++>>-<<<]").unwrap(),
+        vec![
+            MulAdd {
+                offset: 1,
+                factor: 2
+            },
+            MulAdd {
+                offset: 3,
+                factor: -1
+            },
+            Clear,
+            Terminate
+        ]
+    );
+    assert_eq!(
+        parse_instructions("[>>][.]").unwrap(),
+        vec![Scan(2), JumpRight(4), Output, JumpLeft(2), Terminate]
+    );
+    assert!(parse_instructions("[[]").is_err());
+    assert!(parse_instructions("]").is_err());
+}
+
+#[test]
+fn debug_test() {
+    let (code, positions) = parse_debug("+ [-#]").unwrap();
+    assert_eq!(positions, vec![0, 2, 3, 4, 5, 6]);
+    let mut prog = ProgContext::new(code);
+    let mut input = None;
+    while prog.instruction() != Instr::Breakpoint {
+        assert!(prog.step(&mut input).is_none());
+    }
+    assert_eq!(positions[prog.ip()], 4);
+    assert_eq!(prog.tape_window(1), vec![(29_999, 0), (0, 0), (1, 0)]);
+}
+
+#[test]
+fn execution_test() {
+    let hello = "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.";
+    assert_eq!(
+        run(&[hello], ""),
+        (b"Hello World!".to_vec(), ExitCode::Success)
+    );
+    // reverse, piped into a program that adds one to every character
+    assert_eq!(
+        run(&[">,[>,]<[.<]", ",[+.,]"], "abc"),
+        (b"dcb".to_vec(), ExitCode::Success)
+    );
+    assert_eq!(run(&["+[]"], "").1, ExitCode::Timeout);
+}
+
+#[test]
+fn dialect_test() {
+    let mut dialect = Dialect::default();
+    for flag in &[
+        "--cells=u16",
+        "--tape=infinite",
+        "--eof=unchanged",
+        "--no-wrap",
+    ] {
+        dialect.apply_flag(flag).unwrap();
+    }
+    assert_eq!(dialect.cells, CellSize::U16);
+    assert!(dialect.apply_flag("--eof=2").is_err());
+
+    // 256 only fits into bigger cells
+    let big = "++++++++++++++++[>++++++++++++++++<-]>[-.[-]]";
+    assert_eq!(run(&[big], ""), (vec![], ExitCode::Success));
+    assert_eq!(run_dialect(&[big], "", dialect).0, vec![255]);
+    assert_eq!(
+        run_dialect(&["<"], "", dialect).1,
+        ExitCode::PointerOutOfBounds
+    );
+    assert_eq!(run_dialect(&["-"], "", dialect).1, ExitCode::CellOverflow);
+    // the end of the input leaves the cell as it is
+    assert_eq!(run_dialect(&["+++,."], "", dialect).0, vec![3]);
+    // a loop that doesn't run doesn't move the pointer
+    assert_eq!(
+        run_dialect(&["[-<+>]+."], "", dialect),
+        (vec![1], ExitCode::Success)
+    );
+    // errors in between aren't folded away
+    assert_eq!(run_dialect(&["-+"], "", dialect).1, ExitCode::CellOverflow);
+    assert_eq!(
+        run_dialect(&["<>"], "", dialect).1,
+        ExitCode::PointerOutOfBounds
+    );
+    assert_eq!(
+        run_dialect(&["+[-<+->]"], "", dialect).1,
+        ExitCode::PointerOutOfBounds
+    );
+    assert_eq!(
+        parse_dialect("+-", dialect).unwrap(),
+        vec![Instr::Add(1), Instr::Add(-1), Instr::Terminate]
+    );
+}
+
+#[test]
+fn diagnostics_test() {
+    let error = parse_instructions("+[[-]").unwrap_err();
+    assert_eq!(error.position, 1);
+    assert_eq!(excerpt("+[[-]", error.position), "+[[-]\n ^");
+    assert_eq!(parse_instructions("+ ]").unwrap_err().position, 2);
+
+    let mut progs = vec![
+        ProgContext::new(parse_instructions(">>>++[>+.<-]").unwrap()),
+        ProgContext::new(parse_instructions(",[.,]+[]").unwrap()),
+    ];
+    let execution = ProgContext::execute_piped(&mut progs, b"", 0.1, 1);
+    assert_eq!(execution.exit_code, ExitCode::Timeout);
+    assert_eq!(execution.program, 1);
+    assert_eq!(execution.max_cell, Some(4));
+    assert_eq!((execution.output.len(), execution.dropped), (1, 1));
+
+    // cells are only counted once they are read or written
+    for program in &["[-<+>]", "[->>>>>>>>>+<<<<<<<<<]", ">>>"] {
+        let mut progs = [ProgContext::new(parse_instructions(program).unwrap())];
+        let execution = ProgContext::execute_piped(&mut progs, b"", 0.1, 1);
+        assert_eq!(execution.max_cell, Some(0));
+    }
+}
+
+#[test]
+fn limits_test() {
+    let mut progs = vec![
+        ProgContext::new(parse_instructions(">>+").unwrap()),
+        ProgContext::new(parse_instructions(",[.,]+[]").unwrap()),
+    ];
+    let execution = execute(
+        &mut progs,
+        b"",
+        Limit::Time(10.0),
+        1,
+        &AtomicBool::new(true),
+    );
+    assert_eq!(execution.exit_code, ExitCode::Cancelled);
+    // the tapes only have the cells that were used
+    assert_eq!(progs[1].data.len(), 1);
+    assert_eq!(run(&["<+.>.<."], "").0, vec![1, 0, 1]);
+    // a multiply loop that doesn't run doesn't grow the tape to the other end
+    let mut progs = [ProgContext::new(parse_instructions("[-<+>]+.").unwrap())];
+    ProgContext::execute_piped(&mut progs, b"", 1.0, 1);
+    assert_eq!(progs[0].data.len(), 1);
+    assert_eq!(run(&[">>>+<<<.>>>."], "").0, vec![0, 1]);
+}
+
+#[test]
+fn archive_test() {
+    let programs = vec![
+        Archived {
+            name: "reverse".into(),
+            language: Language::Brainfuck,
+            public: true,
+            description: "reverses the input".into(),
+            program: ">,[>,]<[.<]".into(),
+        },
+        Archived {
+            name: "echo".into(),
+            language: Language::Brainfuck,
+            public: false,
+            description: "echoes\nthe input\r\nuntil \\n or EOF".into(),
+            program: "read a byte ,\n[.,] and write it".into(),
+        },
+        Archived {
+            name: "headers".into(),
+            language: Language::Brainfuck,
+            public: false,
+            description: String::new(),
+            program: "!description +\n!program other\n!!,[.,]".into(),
+        },
+        Archived {
+            name: "end".into(),
+            language: Language::Whitespace,
+            public: false,
+            description: String::new(),
+            program: "\n\n\n\n".into(),
+        },
+    ];
+    let archive = to_archive(&programs);
+    assert_eq!(from_archive(&archive).unwrap(), programs);
+    assert!(from_archive("+++").is_err());
+}
+
+#[test]
+fn judge_test() {
+    let cases = vec![
+        TestCase {
+            input: b"ab".to_vec(),
+            output: b"ba".to_vec(),
+        },
+        TestCase {
+            input: unescape("x\\x41\\n").unwrap(),
+            output: b"\nAx".to_vec(),
+        },
+    ];
+    let reverse = parse_instructions(">,[>,]<[.<]").unwrap();
+    assert!(matches!(
+        judge(&reverse, &cases, Limit::Time(1.0), &AtomicBool::new(false)),
+        Verdict::Passed { .. }
+    ));
+    let echo = parse_instructions(",[.,]").unwrap();
+    match judge(&echo, &cases, Limit::Steps(1000), &AtomicBool::new(false)) {
+        Verdict::Failed { case, execution } => {
+            assert_eq!(case, 0);
+            assert_eq!(execution.output, b"ab");
+        }
+        verdict => panic!("{:?}", verdict),
+    }
+    assert!(unescape("\\q").is_err());
+}
+
+#[test]
+fn budget_test() {
+    let execute = |program: &str, budget| {
+        let mut progs = [ProgContext::new(parse_instructions(program).unwrap())];
+        let cancel = AtomicBool::new(false);
+        execute(&mut progs, b"", Limit::Steps(budget), 100, &cancel)
+    };
+    let execution = execute("+[]", 1000);
+    assert_eq!(execution.exit_code, ExitCode::OutOfSteps);
+    assert_eq!(execution.steps, 1000);
+    // the same program always needs the same steps
+    let steps = execute("++++[>++++<-]>[>+>+<<-]>>>+[<]", 1000).steps;
+    assert_eq!(
+        execute("++++[>++++<-]>[>+>+<<-]>>>+[<]", steps).exit_code,
+        ExitCode::Success
+    );
+    assert_eq!(
+        execute("++++[>++++<-]>[>+>+<<-]>>>+[<]", steps - 1).exit_code,
+        ExitCode::OutOfSteps
+    );
+    // a scan stops in the middle once the budget is used up
+    let scan = "+>+>+>+>+>+>+>+<<<<<<<[>]";
+    let steps = execute(scan, 1000).steps;
+    let execution = execute(scan, steps - 3);
+    assert_eq!(execution.exit_code, ExitCode::OutOfSteps);
+    assert_eq!(execution.steps, steps - 3);
+}
@@ -1,334 +1,668 @@
 use super::filter::*;
-use crate::prelude::*;
+use super::golf::*;
 use crate::ser::*;
+use crate::worker::run_job;
+use crate::{prelude::*, tryc};
+use oxidized::brainfuck::*;
+use oxidized::esolang::*;
 use rusqlite::{params, Result};
-use std::collections::*;
-use std::time::*;
+use std::time::Duration;
 
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
-enum ExitCode {
-    Success,
-    Timeout,
-}
+#[group]
+#[checks(Spam)]
+#[commands(brainfuck, befunge, whitespace, ook, bfdebug, store, load, run)]
+pub struct Brainfuck;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Instr {
-    MoveRight,
-    MoveLeft,
-    Increment,
-    Decrement,
-    Output,
-    Input,
-    JumpRight(usize),
-    JumpLeft(usize),
-    Terminate,
-}
+#[group]
+#[prefix = "bflib"]
+#[checks(Spam)]
+#[commands(search)]
+pub struct BrainfuckLibrary;
 
 #[group]
+#[prefix = "bf"]
 #[checks(Spam)]
-#[commands(brainfuck, store, load, run)]
-pub struct Brainfuck;
+#[commands(
+    delete, rename, describe, export, import, challenge, testcase, challenges, submit, golf
+)]
+pub struct BrainfuckPrograms;
+
+/// How many programs a search lists
+const LISTED_PROGRAMS: usize = 15;
+
+/// Largest archive that is imported, in bytes
+const MAX_ARCHIVE: u32 = 1 << 16;
+
+/// How long programs run without `--steps`, in seconds
+const TIME_LIMIT: f64 = 1.0;
+
+/// Budget of `--steps` without a number
+const DEFAULT_STEPS: usize = 10_000_000;
+
+/// Largest budget of `--steps`
+const MAX_STEPS: usize = 100_000_000;
 
 #[command]
 #[min_args(1)]
-#[description = "Executes a brainfuck program. See [here](https://esolangs.org/wiki/Brainfuck) for an introduction to the language."]
-#[usage = "<program> <input>"]
+#[description = "Executes a brainfuck program. See [here](https://esolangs.org/wiki/Brainfuck) for an introduction to the language. Flags in front of the program change the dialect: `--cells=u8|u16|u32`, `--tape=<cells>|infinite`, `--eof=0|-1|unchanged` and `--no-wrap`, which makes cells and the pointer stop at the ends instead of wrapping around. `--steps[=<n>]` gives the program an exact budget of steps instead of a second, so it always ends the same way, as long as it doesn't run longer than 30 seconds. The program can be put in a code block."]
+#[usage = "[flags] <program> <input>"]
 #[example = ",[.,]. echo"]
 #[bucket("brainfuck")]
 async fn brainfuck(ctx: &Context, msg: &Message) -> CommandResult {
+    exec_language(ctx, msg, Language::Brainfuck).await
+}
+
+#[command]
+#[min_args(1)]
+#[description = "Executes a [Befunge-93](https://esolangs.org/wiki/Befunge) program, which runs on a grid of 80×25 characters. Put programs with more than one line or with spaces in a code block. `--steps[=<n>]` works like for brainfuck."]
+#[usage = "[flags] <program> <input>"]
+#[example = "~:1+!#@_, echo"]
+#[bucket("brainfuck")]
+async fn befunge(ctx: &Context, msg: &Message) -> CommandResult {
+    exec_language(ctx, msg, Language::Befunge).await
+}
+
+#[command]
+#[min_args(1)]
+#[description = "Executes a [Whitespace](https://esolangs.org/wiki/Whitespace) program, which only consists of spaces, tabs and line breaks, so it has to be put in a code block. `--steps[=<n>]` works like for brainfuck."]
+#[usage = "[flags] <program> <input>"]
+#[bucket("brainfuck")]
+async fn whitespace(ctx: &Context, msg: &Message) -> CommandResult {
+    exec_language(ctx, msg, Language::Whitespace).await
+}
+
+#[command]
+#[min_args(1)]
+#[description = "Executes an [Ook!](https://esolangs.org/wiki/Ook!) program, brainfuck where every instruction is a pair of `Ook.`, `Ook?` and `Ook!`. Put the program in a code block. The flags of brainfuck work as well."]
+#[usage = "[flags] <program> <input>"]
+#[bucket("brainfuck")]
+async fn ook(ctx: &Context, msg: &Message) -> CommandResult {
+    exec_language(ctx, msg, Language::Ook).await
+}
+
+/// Runs a single program of a language, given with its flags and input
+async fn exec_language(ctx: &Context, msg: &Message, language: Language) -> CommandResult {
     let mut args = msg.args();
+    let (dialect, limit) = flags(&mut args)?;
+    let (program, input) = program_and_input(&mut args)?;
+    let programs = [(String::from("program"), language, program)];
+    make_exec(ctx, msg, &input, &programs, dialect, limit).await
+}
+
+/// Reads the program, which is a single argument or a code block, and the input after it
+fn program_and_input(args: &mut Args) -> CommandResult<(String, String)> {
+    let rest = args.rest().trim_start();
+    if let Some(block) = rest.strip_prefix("```") {
+        let end = block.find("```").ok_or("The code block isn't closed.")?;
+        let program = code_block(&rest[..end + 6]).to_string();
+        let input = block[end + 3..].trim_start().to_string();
+        return Ok((program, input));
+    }
     let program = args.single::<String>()?;
-    let input = args.rest();
-    make_exec(ctx, msg, &input, &[program]).await
+    Ok((program, args.rest().to_string()))
 }
 
+/// Reads the flags that change the dialect, in front of the other arguments
+fn dialect_flags(args: &mut Args) -> CommandResult<Dialect> {
+    let mut dialect = Dialect::default();
+    while let Some(flag) = args.current().filter(|arg| arg.starts_with("--")) {
+        dialect.apply_flag(flag)?;
+        args.advance();
+    }
+    Ok(dialect)
+}
+
+/// Reads the flags that change the dialect or the limit, in front of the other arguments
+fn flags(args: &mut Args) -> CommandResult<(Dialect, Limit)> {
+    let mut dialect = Dialect::default();
+    let mut limit = Limit::Time(TIME_LIMIT);
+    while let Some(flag) = args.current().filter(|arg| arg.starts_with("--")) {
+        match flag.split_once('=') {
+            _ if flag == "--steps" => limit = Limit::Steps(DEFAULT_STEPS),
+            Some(("--steps", budget)) => match budget.parse() {
+                Ok(budget) if budget > 0 && budget <= MAX_STEPS => limit = Limit::Steps(budget),
+                _ => Err(format!(
+                    "the budget is a number of steps up to {}",
+                    MAX_STEPS
+                ))?,
+            },
+            _ => dialect.apply_flag(flag)?,
+        }
+        args.advance();
+    }
+    Ok((dialect, limit))
+}
+
+/// Runs programs that are piped into each other, each program has a name, a language and its code
 async fn make_exec(
     ctx: &Context,
     msg: &Message,
     input: &str,
-    programs: &[String],
+    programs: &[(String, Language, String)],
+    dialect: Dialect,
+    limit: Limit,
 ) -> CommandResult {
     assert!(programs.len() > 0);
 
     let mut progs = Vec::new();
-    for program in programs.iter() {
-        let program = parse_instructions(&program)?;
-        let program = ProgContext::new(program);
-        progs.push(program);
+    for (name, language, program) in programs.iter() {
+        match language.compile(program, dialect) {
+            Ok(program) => progs.push(program),
+            Err(why) => {
+                let error = format!("`{}`: {}", name, why.excerpt(program));
+                return msg.err_reply(ctx, &error).await;
+            }
+        }
     }
 
-    let (iter, output, exit_code) =
-        ProgContext::execute_piped(&mut progs, input.as_bytes(), 1.0, 1000);
-    let mut output = String::from_utf8_lossy(&output);
+    let input = input.as_bytes().to_vec();
+    let execution = run_job(ctx, msg, move |cancel| {
+        execute(&mut progs, &input, limit, 1000, cancel)
+    })
+    .await?;
+    let execution = match execution {
+        Some(execution) => execution,
+        None => return Ok(()),
+    };
+    let mut output = String::from_utf8_lossy(&execution.output);
 
     if output.len() == 0 {
         output = "\u{200b}".into();
     }
 
+    let mut info = match limit {
+        Limit::Steps(budget) => format!(
+            "{:?} after {} of {} steps ({:.1}% of the budget)",
+            execution.exit_code,
+            execution.steps,
+            budget,
+            execution.steps as f64 / budget as f64 * 100.0
+        ),
+        Limit::Time(_) => format!("{:?} after {} steps", execution.exit_code, execution.steps),
+    };
+    if programs.len() > 1 && execution.exit_code != ExitCode::Success {
+        info += &format!(" in `{}`", programs[execution.program].0);
+    }
+    if let Some(max_cell) = execution.max_cell {
+        info += &format!("\nHighest cell used: {}", max_cell);
+    }
+    if execution.dropped > 0 {
+        info += &format!("\n{} bytes of the output were cut off", execution.dropped);
+    }
+
+    let language = programs[0].1;
+    let title = if programs.iter().all(|(_, other, _)| *other == language) {
+        format!("{} Program Execution", language.title())
+    } else {
+        String::from("Program Execution")
+    };
     msg.ereply(ctx, |e| {
-        e.title("Brainfuck Program Execution");
-        e.field("Output", output, false);
-        e.field("Exit Info", format!("{:?}({})", exit_code, iter), false)
+        e.title(title)
+            .field("Output", output, false)
+            .field("Exit Info", info, false)
     })
     .await?;
 
     Ok(())
 }
 
-fn parse_instructions(string: &str) -> Result<Vec<Instr>, &'static str> {
-    let mut program = Vec::new();
-    let mut stack = VecDeque::new();
-    for ch in string.chars() {
-        let idx = program.len();
-        let instr = match ch {
-            '>' => Instr::MoveRight,
-            '<' => Instr::MoveLeft,
-            '+' => Instr::Increment,
-            '-' => Instr::Decrement,
-            '.' => Instr::Output,
-            ',' => Instr::Input,
-            '[' => {
-                stack.push_back(idx);
-                Instr::JumpRight(0)
-            }
-            ']' => {
-                let target = match stack.pop_back() {
-                    Some(t) => t,
-                    None => return Err("mismatched brackets"),
-                };
-                match program[target] {
-                    Instr::JumpRight(ref mut target) => *target = idx + 1,
-                    _ => unreachable!(),
-                };
-                Instr::JumpLeft(target + 1)
-            }
-            _ => continue,
-        };
-        program.push(instr);
-    }
-    if !stack.is_empty() {
-        Err("mismatched brackets")
-    } else {
-        program.push(Instr::Terminate);
-        Ok(program)
-    }
-}
-
-struct ProgContext {
-    code: Vec<Instr>,
-    data: Vec<u8>,
-    ptr: usize,
-    ip: usize,
+/// Reactions that control the debugger, with what they do
+const DEBUG_CONTROLS: [(&str, &str); 5] = [
+    ("👣", "1 step"),
+    ("🔟", "10 steps"),
+    ("💯", "100 steps"),
+    ("🔤", "to the next `.` or `,`"),
+    ("🔴", "to the next `#`"),
+];
+
+/// How many steps the debugger makes at most for a single reaction
+const DEBUG_STEP_LIMIT: usize = 100_000;
+
+/// How long the debugger waits for a reaction
+const DEBUG_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A program that is run step by step
+struct Debugger {
+    prog: ProgContext,
+    /// position of every instruction in the source
+    positions: Vec<usize>,
+    source: String,
+    input: Vec<u8>,
+    input_pos: usize,
+    output: Vec<u8>,
+    steps: usize,
+    finished: bool,
+    /// why the program crashed
+    error: Option<ExitCode>,
 }
 
-#[derive(Copy, Clone)]
-enum Output {
-    Starved,
-    Terminated,
-    Timeout,
-    Value(u8),
-}
+impl Debugger {
+    fn new(source: &str, input: &str, dialect: Dialect) -> Result<Self, ParseError> {
+        let (code, positions) = parse_debug(source)?;
+        Ok(Self {
+            prog: ProgContext::with_dialect(code, dialect),
+            positions,
+            source: source.into(),
+            input: input.as_bytes().to_vec(),
+            input_pos: 0,
+            output: Vec::new(),
+            steps: 0,
+            finished: false,
+            error: None,
+        })
+    }
 
-impl ProgContext {
-    fn new(code: Vec<Instr>) -> Self {
-        Self {
-            code,
-            data: vec![0u8; 30_000],
-            ptr: 0,
-            ip: 0,
+    /// executes one instruction, reading the input if the program needs it
+    fn step(&mut self) {
+        let mut input = None;
+        let mut output = self.prog.step(&mut input);
+        if let Some(Output::Starved) = output {
+            input = Some(match self.input.get(self.input_pos) {
+                Some(&byte) => Input::Byte(byte),
+                None => Input::Eof,
+            });
+            self.input_pos += 1;
+            output = self.prog.step(&mut input);
+        }
+        match output {
+            Some(Output::Value(val)) if self.output.len() < 1000 => self.output.push(val),
+            Some(Output::Terminated) => self.finished = true,
+            Some(Output::Error(exit_code)) => {
+                self.finished = true;
+                self.error = Some(exit_code);
+            }
+            _ => {}
         }
+        self.steps += 1;
     }
 
-    fn next_output(&mut self, input: &mut Option<u8>, iter: &mut usize, until: Instant) -> Output {
-        while Instant::now() < until {
-            *iter += 1;
-            let instr = self.code[self.ip];
-            match instr {
-                Instr::MoveRight => self.ptr = (self.ptr + 1) % self.data.len(),
-                Instr::MoveLeft => self.ptr = (self.ptr + self.data.len() - 1) % self.data.len(),
-                Instr::Increment => self.data[self.ptr] = self.data[self.ptr].wrapping_add(1),
-                Instr::Decrement => self.data[self.ptr] = self.data[self.ptr].wrapping_sub(1),
-                Instr::Input => match input {
-                    None => return Output::Starved,
-                    Some(val) => {
-                        self.data[self.ptr] = *val;
-                        *input = None;
-                    }
-                },
-                Instr::Output => {
-                    self.ip += 1;
-                    return Output::Value(self.data[self.ptr]);
-                }
-                Instr::JumpRight(target) => {
-                    if self.data[self.ptr] == 0 {
-                        self.ip = target;
-                        continue;
-                    }
-                }
-                Instr::JumpLeft(target) => {
-                    if self.data[self.ptr] > 0 {
-                        self.ip = target;
-                        continue;
-                    }
-                }
-                Instr::Terminate => return Output::Terminated,
+    /// runs the program as far as the control of the reaction says
+    fn run(&mut self, control: usize) {
+        let limit = [1, 10, 100]
+            .get(control)
+            .copied()
+            .unwrap_or(DEBUG_STEP_LIMIT);
+        for n in 0..limit {
+            if self.finished {
+                break;
             }
-            self.ip += 1;
+            let instr = self.prog.instruction();
+            let stop = match control {
+                3 => instr == Instr::Output || instr == Instr::Input,
+                4 => instr == Instr::Breakpoint,
+                _ => false,
+            };
+            // the instruction the debugger stopped at last time is executed
+            if n > 0 && stop {
+                break;
+            }
+            self.step();
         }
-        Output::Timeout
-    }
-
-    fn execute_piped(
-        progs: &mut [Self],
-        user_input: &[u8],
-        time_limit: f64,
-        char_limit: usize,
-    ) -> (usize, Vec<u8>, ExitCode) {
-        assert!(progs.len() > 0);
-
-        let end = Instant::now() + Duration::from_secs_f64(time_limit);
-        let mut pid = progs.len() - 1;
-        let mut iter = 0;
-
-        let mut user_input = user_input.iter().chain(Some(0).iter().cycle());
-        let mut prog_input = vec![None; progs.len()];
-
-        let mut output = Vec::new();
-
-        loop {
-            let out = progs[pid].next_output(&mut prog_input[pid], &mut iter, end);
-            match out {
-                Output::Starved => {
-                    if pid == 0 {
-                        prog_input[pid] = Some(*user_input.next().unwrap());
-                    } else {
-                        pid -= 1;
-                    }
-                }
-                Output::Terminated => {
-                    if pid == progs.len() - 1 {
-                        return (iter, output, ExitCode::Success);
-                    } else {
-                        prog_input[pid + 1] = Some(0);
-                        pid += 1;
-                    }
-                }
-                Output::Timeout => {
-                    return (iter, output, ExitCode::Timeout);
-                }
-                Output::Value(val) => {
-                    if pid == progs.len() - 1 {
-                        if output.len() < char_limit {
-                            output.push(val);
-                        }
-                    } else {
-                        prog_input[pid + 1] = Some(val);
-                        pid += 1;
-                    }
+    }
+
+    /// the part of the program around the next instruction, with a caret below it
+    fn excerpt(&self) -> String {
+        excerpt(&self.source, self.positions[self.prog.ip()])
+    }
+
+    fn tape(&self) -> String {
+        self.prog
+            .tape_window(4)
+            .into_iter()
+            .map(|(cell, value)| {
+                if cell == self.prog.ptr() {
+                    format!("[{:>3}]", value)
+                } else {
+                    format!(" {:>3} ", value)
                 }
-            }
+            })
+            .collect()
+    }
+
+    fn show(&self, e: CreateEmbed) -> CreateEmbed {
+        let mut output = String::from_utf8_lossy(&self.output).into_owned();
+        if output.is_empty() {
+            output = "\u{200b}".into();
         }
+        let status = match self.error {
+            Some(exit_code) => format!("crashed with {:?}", exit_code),
+            None if self.finished => String::from("terminated"),
+            None => String::from("running"),
+        };
+        let controls = DEBUG_CONTROLS
+            .iter()
+            .map(|(emoji, control)| format!("{} {}", emoji, control))
+            .collect::<Vec<_>>()
+            .join(", ");
+        e.title("Brainfuck Debugger")
+            .description(format!(
+                "```\n{}\n```\nTape around cell {}:\n```\n{}\n```",
+                self.excerpt(),
+                self.prog.ptr(),
+                self.tape()
+            ))
+            .field("Output", output, false)
+            .field(
+                "Status",
+                format!("{} after {} steps\n{}", status, self.steps, controls),
+                false,
+            )
     }
 }
 
+#[command]
+#[min_args(1)]
+#[description = "Runs a brainfuck program step by step and shows the tape around the pointer. React to make steps, to run to the next input or output, or to run to the next breakpoint `#`. The program can be put in a code block."]
+#[usage = "[flags] <program> <input>"]
+#[example = "+++[>++#<-]>. input"]
+#[bucket("brainfuck")]
+async fn bfdebug(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut args = msg.args();
+    let dialect = dialect_flags(&mut args)?;
+    let (program, input) = program_and_input(&mut args)?;
+    let mut debugger = match Debugger::new(&program, &input, dialect) {
+        Ok(debugger) => debugger,
+        Err(why) => return msg.err_reply(ctx, &why.excerpt(&program)).await,
+    };
+
+    let mut board = msg.ereply(ctx, |e| debugger.show(e)).await?;
+    for (emoji, _) in DEBUG_CONTROLS.iter() {
+        board
+            .react(ctx, ReactionType::Unicode(String::from(*emoji)))
+            .await?;
+    }
+
+    let author = msg.author.id;
+    while !debugger.finished {
+        let reaction = board
+            .await_reaction(ctx)
+            .timeout(DEBUG_TIMEOUT)
+            .filter(move |r| r.user_id == Some(author))
+            .await;
+        let reaction = match reaction {
+            Some(reaction) => reaction,
+            None => break,
+        };
+        let control = DEBUG_CONTROLS
+            .iter()
+            .position(|(emoji, _)| reaction.emoji.unicode_eq(emoji));
+        let control = tryc!(control);
+        // so the same control can be used again
+        reaction.delete(ctx).await.ok();
+
+        debugger.run(control);
+        board.eedit(ctx, |e| debugger.show(e)).await?;
+    }
+
+    board.delete_reactions(ctx).await.ok();
+    Ok(())
+}
+
+/// Columns that were added to the table later, older tables don't have them yet
+const ADDED_COLUMNS: [&str; 5] = [
+    "author_name TEXT NOT NULL DEFAULT ''",
+    "public INTEGER NOT NULL DEFAULT 0",
+    "description TEXT NOT NULL DEFAULT ''",
+    "uses INTEGER NOT NULL DEFAULT 0",
+    "language TEXT NOT NULL DEFAULT 'brainfuck'",
+];
+
 fn create_table() -> Result<()> {
     db()?.execute(
-        "CREATE TABLE IF NOT EXISTS brainfuck (author TEXT, name TEXT, program TEXT);",
+        &format!(
+            "CREATE TABLE IF NOT EXISTS brainfuck (author TEXT, name TEXT, program TEXT, {});",
+            ADDED_COLUMNS.join(", ")
+        ),
         params!(),
     )?;
     Ok(())
 }
 
+/// Adds the columns that older tables don't have yet, runs once when the bot starts
+pub fn migrate_table() -> Result<()> {
+    create_table()?;
+    let db = db()?;
+    for column in ADDED_COLUMNS.iter() {
+        let added = db.execute(
+            &format!("ALTER TABLE brainfuck ADD COLUMN {};", column),
+            params!(),
+        );
+        match added {
+            // the table has the column already
+            Err(rusqlite::Error::SqliteFailure(_, Some(message)))
+                if message.starts_with("duplicate column name") => {}
+            added => {
+                added?;
+            }
+        }
+    }
+    Ok(())
+}
+
 #[command]
 #[min_args(2)]
-#[max_args(2)]
-#[description = "Stores a brainfuck program for later or repeated usage."]
-#[usage = "<name> <program>"]
-#[example = "reverse >,[>,]<[.<]"]
-pub async fn store(_: &Context, msg: &Message) -> CommandResult {
+#[description = "Stores a program for later or repeated usage. The program can contain spaces and comments and can be put in a code block. With `--public`, everyone can run it as `@you/name`. Programs are brainfuck unless `--lang=befunge|whitespace|ook` says otherwise."]
+#[usage = "[--public] [--lang=<language>] <name> <program>"]
+#[example = "--public reverse >,[>,]<[.<]"]
+pub async fn store(ctx: &Context, msg: &Message) -> CommandResult {
     let mut args = msg.args();
+    let mut public = false;
+    let mut language = Language::Brainfuck;
+    while let Some(flag) = args.current().filter(|arg| arg.starts_with("--")) {
+        match flag.split_once('=') {
+            _ if flag == "--public" => public = true,
+            Some(("--lang", name)) => {
+                language =
+                    Language::from_name(name).ok_or(format!("There is no language `{}`.", name))?
+            }
+            _ => Err(format!("Unknown flag `{}`", flag))?,
+        }
+        args.advance();
+    }
     let name = args.single::<String>()?;
-    let program = args.single::<String>()?;
+    let program = code_block(args.rest());
 
-    if name.as_str().chars().any(|ch| !ch.is_ascii_alphabetic()) {
-        Err("Invalid program name")?;
+    check_name(&name)?;
+    if let Err(why) = language.compile(program, Dialect::default()) {
+        return msg.err_reply(ctx, &why.excerpt(program)).await;
+    }
+
+    save_program(&msg.author, &name, language, program, public)?;
+    Ok(())
+}
+
+/// The code inside a code block, or the whole text if it isn't one.
+/// The inside isn't trimmed, whitespace matters for some languages.
+pub fn code_block(text: &str) -> &str {
+    let text = text.trim();
+    match text
+        .strip_prefix("```")
+        .and_then(|text| text.strip_suffix("```"))
+    {
+        Some(code) => {
+            // the first line of a code block can name its language
+            let code = match code.split_once('\n') {
+                Some((language, code)) if language.chars().all(|ch| ch.is_ascii_alphabetic()) => {
+                    code
+                }
+                _ => code,
+            };
+            // the line break in front of the closing backticks
+            code.strip_suffix('\n').unwrap_or(code)
+        }
+        None => text,
     }
-    if program.as_str().chars().any(|ch| !",.<>+-[]".contains(ch)) {
-        Err("Invalid program")?;
+}
+
+fn check_name(name: &str) -> CommandResult {
+    if name.is_empty() || name.chars().any(|ch| !ch.is_ascii_alphabetic()) {
+        Err("Invalid program name")?;
     }
+    Ok(())
+}
 
-    let author = format!("{}", msg.author.id);
+/// Stores a program or replaces the program with the same name, the description is kept
+fn save_program(
+    author: &User,
+    name: &str,
+    language: Language,
+    program: &str,
+    public: bool,
+) -> Result<()> {
+    let id = format!("{}", author.id);
 
     create_table()?;
     let db = db()?;
 
     let affected = db.execute(
-        "UPDATE brainfuck SET program = ?3 WHERE author=?1 AND name = ?2;",
-        params!(author, name, program),
+        "UPDATE brainfuck SET program = ?3, author_name = ?4, public = ?5, language = ?6 WHERE author=?1 AND name = ?2;",
+        params!(id, name, program, author.name, public, language.name()),
     )?;
 
     if affected == 0 {
         db.execute(
-            "INSERT INTO brainfuck (author, name, program) VALUES (?1, ?2, ?3)",
-            params!(author, name, program),
+            "INSERT INTO brainfuck (author, name, program, author_name, public, language) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params!(id, name, program, author.name, public, language.name()),
         )?;
     }
-
+    // the other programs are found by the current name as well
+    db.execute(
+        "UPDATE brainfuck SET author_name = ?2 WHERE author = ?1",
+        params!(id, author.name),
+    )?;
     Ok(())
 }
 
-fn load_program(name: &str, msg: &Message) -> CommandResult<String> {
-    let author = format!("{}", msg.author.id);
-    if name.chars().any(|ch| !ch.is_ascii_alphabetic()) {
-        Err("Invalid program name")?;
+/// A program from the database
+struct StoredProgram {
+    author: String,
+    name: String,
+    language: Language,
+    program: String,
+}
+
+/// The language stored in a column, programs from before there were other languages are brainfuck
+fn stored_language(name: String) -> Language {
+    Language::from_name(&name).unwrap_or(Language::Brainfuck)
+}
+
+impl StoredProgram {
+    fn count_use(&self) -> Result<()> {
+        db()?.execute(
+            "UPDATE brainfuck SET uses = uses + 1 WHERE author = ?1 AND name = ?2",
+            params!(self.author, self.name),
+        )?;
+        Ok(())
+    }
+}
+
+/// The id of the person that `@user` or a mention in front of a program name stands for.
+/// Names are looked up on the server first, then among the names people stored programs with,
+/// which have to be unique.
+fn resolve_owner(ctx: &Context, msg: &Message, owner: &str) -> CommandResult<String> {
+    if let Some(id) = serenity::utils::parse_user_mention(owner) {
+        return Ok(format!("{}", id));
+    }
+    let name = owner.trim_start_matches('@');
+
+    let member = msg.guild_id.and_then(|server| {
+        let server = ctx.cache.guild(server)?;
+        server.member_named(name).map(|member| member.user.id)
+    });
+    if let Some(id) = member {
+        return Ok(format!("{}", id));
     }
 
     create_table()?;
     let db = db()?;
-    let program: String = db.query_row(
-        "SELECT program FROM brainfuck WHERE author = ?1 AND name = ?2",
-        params!(author, name),
-        |row| row.get(0),
-    )?;
+    let mut stmt =
+        db.prepare("SELECT DISTINCT author FROM brainfuck WHERE author_name = ?1 COLLATE NOCASE")?;
+    let authors = stmt
+        .query_map(params!(name), |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    match authors.as_slice() {
+        [author] => Ok(author.clone()),
+        [] => Err(format!("There is nobody called `{}`.", name).into()),
+        _ => Err(format!(
+            "Several people are called `{}`, mention the one you mean.",
+            name
+        )
+        .into()),
+    }
+}
+
+/// Loads an own program by its name, or a program of someone else by `@user/name`,
+/// which only works for public programs
+fn load_program(ctx: &Context, reference: &str, msg: &Message) -> CommandResult<StoredProgram> {
+    let own = format!("{}", msg.author.id);
+    let (owner, name) = match reference.rsplit_once('/') {
+        Some((owner, name)) => (resolve_owner(ctx, msg, owner)?, name),
+        None => (own.clone(), reference),
+    };
+    check_name(name)?;
+
+    create_table()?;
+    let program = db()?
+        .query_row(
+            "SELECT author, name, program, language FROM brainfuck WHERE author = ?1 AND name = ?2 AND (public OR author = ?3)",
+            params!(owner, name, own),
+            |row| {
+                Ok(StoredProgram {
+                    author: row.get(0)?,
+                    name: row.get(1)?,
+                    program: row.get(2)?,
+                    language: stored_language(row.get(3)?),
+                })
+            },
+        )
+        .map_err(|_| format!("There is no program `{}`.", reference))?;
     Ok(program)
 }
 
+/// names of the programs stored by a user
+pub fn program_names(author: UserId) -> Result<Vec<String>> {
+    create_table()?;
+    let db = db()?;
+    let mut stmt = db.prepare("SELECT name FROM brainfuck WHERE author = ?1")?;
+    let names = stmt
+        .query_map(params!(format!("{}", author)), |row| {
+            row.get::<_, String>(0)
+        })?
+        .collect();
+    names
+}
+
 #[command]
 #[max_args(1)]
-#[description = "Loads a stored brainfuck program and displays it to you, or displays the names of all stored programs."]
+#[description = "Loads a stored program and displays it to you, or displays the names of all stored programs. Public programs of others are loaded with `@user/name`."]
 #[usage = "[<name>]"]
 #[example = "reverse"]
 pub async fn load(ctx: &Context, msg: &Message) -> CommandResult {
     let mut args = msg.args();
     match args.single::<String>() {
         Ok(name) => {
-            let program = load_program(&name, msg)?;
+            let program = load_program(ctx, &name, msg)?;
+            let title = format!("{}.{}", name, program.language.extension());
+            let program = program.program;
             msg.ereply(ctx, |e| {
-                e.title(format!("{}.bf", name));
-                e.field("\u{200b}", format!("```\n{}\n```", program), false)
+                e.title(title)
+                    .field("\u{200b}", format!("```\n{}\n```", program), false)
             })
             .await?;
         }
         Err(_) => {
             let programs = {
-                let db = db()?;
-                let mut stmt = db.prepare("SELECT name FROM brainfuck WHERE author = ?1")?;
-                let program_iter = stmt
-                    .query_map(params!(format!("{}", msg.author.id)), |row| {
-                        Ok(row.get::<_, String>(0)?)
-                    })?;
-
                 let mut programs = String::from("```\n");
-                for program in program_iter {
-                    programs += &program?;
+                for program in program_names(msg.author.id)? {
+                    programs += &program;
                     programs += "\n";
                 }
                 programs + "```"
             };
 
             msg.ereply(ctx, |e| {
-                e.title("Your stored brainfuck programs");
-                e.field("\u{200b}", programs, false)
+                e.title("Your stored programs")
+                    .field("\u{200b}", programs, false)
             })
             .await?;
         }
@@ -336,14 +670,258 @@ pub async fn load(ctx: &Context, msg: &Message) -> CommandResult {
     Ok(())
 }
 
+#[command]
+#[description = "Searches the public programs by their name and description, the most used first. Without a search term, the most used programs are listed."]
+#[usage = "[term]"]
+#[example = "reverse"]
+pub async fn search(ctx: &Context, msg: &Message) -> CommandResult {
+    let term = msg.args().rest().to_string();
+
+    create_table()?;
+    let lines = {
+        let db = db()?;
+        let mut stmt = db.prepare(
+            "SELECT author_name, name, description, uses, language FROM brainfuck
+            WHERE public AND (instr(lower(name), lower(?1)) OR instr(lower(description), lower(?1)))
+            ORDER BY uses DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params!(term, LISTED_PROGRAMS as i64), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+                stored_language(row.get(4)?),
+            ))
+        })?;
+
+        let mut lines = Vec::new();
+        for row in rows {
+            let (author, name, description, uses, language) = tryc!(row.ok());
+            lines.push(format!(
+                "`@{}/{}` ({}, {} uses) {}\n",
+                author,
+                name,
+                language.title(),
+                uses,
+                description
+            ));
+        }
+        lines
+    };
+    let lines = split_into_fields(&lines, "No public programs were found.");
+
+    msg.ereply(ctx, |e| {
+        e.title("Public programs").description(lines[0].clone())
+    })
+    .await?;
+    Ok(())
+}
+
 #[command]
 #[min_args(1)]
-#[description = "Loads a stored brainfuck program and runs it on an input given by you."]
-#[usage = "<name> <input>"]
+#[max_args(1)]
+#[description = "Deletes one of your stored brainfuck programs."]
+#[usage = "<name>"]
+#[example = "reverse"]
+pub async fn delete(ctx: &Context, msg: &Message) -> CommandResult {
+    let name = msg.args().single::<String>()?;
+
+    create_table()?;
+    let affected = db()?.execute(
+        "DELETE FROM brainfuck WHERE author = ?1 AND name = ?2",
+        params!(format!("{}", msg.author.id), name),
+    )?;
+    if affected == 0 {
+        return msg
+            .err_reply(ctx, &format!("You have no program `{}`.", name))
+            .await;
+    }
+    Ok(())
+}
+
+#[command]
+#[min_args(2)]
+#[max_args(2)]
+#[description = "Renames one of your stored brainfuck programs. Others have to use the new name to run it."]
+#[usage = "<name> <new name>"]
+#[example = "reverse rev"]
+pub async fn rename(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut args = msg.args();
+    let name = args.single::<String>()?;
+    let new_name = args.single::<String>()?;
+    check_name(&new_name)?;
+
+    let author = format!("{}", msg.author.id);
+    create_table()?;
+    let db = db()?;
+    let taken = db
+        .query_row(
+            "SELECT name FROM brainfuck WHERE author = ?1 AND name = ?2",
+            params!(author, new_name),
+            |_| Ok(()),
+        )
+        .is_ok();
+    if taken {
+        return msg
+            .err_reply(ctx, &format!("You already have a program `{}`.", new_name))
+            .await;
+    }
+
+    let affected = db.execute(
+        "UPDATE brainfuck SET name = ?3 WHERE author = ?1 AND name = ?2",
+        params!(author, name, new_name),
+    )?;
+    if affected == 0 {
+        return msg
+            .err_reply(ctx, &format!("You have no program `{}`.", name))
+            .await;
+    }
+    Ok(())
+}
+
+#[command]
+#[min_args(1)]
+#[description = "Sets the description of one of your stored brainfuck programs, which is shown in the library search. Without a text, the description is removed."]
+#[usage = "<name> [text]"]
+#[example = "reverse reverses the input"]
+pub async fn describe(ctx: &Context, msg: &Message) -> CommandResult {
+    let mut args = msg.args();
+    let name = args.single::<String>()?;
+    let description = args.rest().trim();
+
+    create_table()?;
+    let affected = db()?.execute(
+        "UPDATE brainfuck SET description = ?3 WHERE author = ?1 AND name = ?2",
+        params!(format!("{}", msg.author.id), name, description),
+    )?;
+    if affected == 0 {
+        return msg
+            .err_reply(ctx, &format!("You have no program `{}`.", name))
+            .await;
+    }
+    Ok(())
+}
+
+#[command]
+#[max_args(0)]
+#[description = "Sends all of your stored brainfuck programs as a `.bf` archive by direct message, which can be imported again."]
+pub async fn export(ctx: &Context, msg: &Message) -> CommandResult {
+    create_table()?;
+    let programs = {
+        let db = db()?;
+        let mut stmt = db.prepare(
+            "SELECT name, public, description, program, language FROM brainfuck WHERE author = ?1 ORDER BY name",
+        )?;
+        let rows = stmt.query_map(params!(format!("{}", msg.author.id)), |row| {
+            Ok(Archived {
+                name: row.get(0)?,
+                public: row.get(1)?,
+                description: row.get(2)?,
+                program: row.get(3)?,
+                language: stored_language(row.get(4)?),
+            })
+        })?;
+
+        let mut programs = Vec::new();
+        for row in rows {
+            programs.push(tryc!(row.ok()));
+        }
+        programs
+    };
+
+    // the archive contains the private programs as well, so it isn't posted in the channel
+    let file = CreateAttachment::bytes(to_archive(&programs).into_bytes(), "programs.bf");
+    let embed = embed_template(msg)
+        .title("Your stored programs")
+        .description(format!("{} programs", programs.len()));
+    let sent = msg
+        .author
+        .direct_message(ctx, CreateMessage::new().embed(embed).add_file(file))
+        .await;
+    if sent.is_err() {
+        return msg
+            .err_reply(ctx, "I can't send you a direct message.")
+            .await;
+    }
+    if msg.guild_id.is_some() {
+        msg.ereply(ctx, |e| {
+            e.title("Your stored programs")
+                .description("I sent them to you by direct message.")
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+#[command]
+#[max_args(0)]
+#[description = "Stores all programs of a `.bf` archive attached to the message, like the one `export` sends. Programs with the same name are replaced."]
+pub async fn import(ctx: &Context, msg: &Message) -> CommandResult {
+    let attachment = match msg.attachments.first() {
+        Some(attachment) => attachment,
+        None => return msg.err_reply(ctx, "Attach a `.bf` archive.").await,
+    };
+    if attachment.size > MAX_ARCHIVE {
+        return msg.err_reply(ctx, "The archive is too big.").await;
+    }
+    let archive = String::from_utf8(attachment.download().await?)?;
+
+    let programs = match from_archive(&archive) {
+        Ok(programs) => programs,
+        Err(why) => return msg.err_reply(ctx, &why).await,
+    };
+    for program in programs.iter() {
+        check_name(&program.name)?;
+        if let Err(why) = program
+            .language
+            .compile(&program.program, Dialect::default())
+        {
+            let error = format!("`{}`: {}", program.name, why.excerpt(&program.program));
+            return msg.err_reply(ctx, &error).await;
+        }
+    }
+
+    for program in programs.iter() {
+        save_program(
+            &msg.author,
+            &program.name,
+            program.language,
+            &program.program,
+            program.public,
+        )?;
+        db()?.execute(
+            "UPDATE brainfuck SET description = ?3 WHERE author = ?1 AND name = ?2",
+            params!(
+                format!("{}", msg.author.id),
+                program.name,
+                program.description
+            ),
+        )?;
+    }
+
+    msg.ereply(ctx, |e| {
+        e.title("Imported programs").description(
+            programs
+                .iter()
+                .map(|program| format!("`{}`", program.name))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    })
+    .await?;
+    Ok(())
+}
+
+#[command]
+#[min_args(1)]
+#[description = "Loads a stored program and runs it on an input given by you. Programs separated by `|` are piped into each other, even if they are written in different languages, public programs of others are used with `@user/name`."]
+#[usage = "[flags] <name> <input>"]
 #[example = "reverse Hello, World!"]
 #[bucket("brainfuck")]
 pub async fn run(ctx: &Context, msg: &Message) -> CommandResult {
     let mut args = msg.args();
+    let (dialect, limit) = flags(&mut args)?;
 
     let prog_names = args.single::<String>()?;
     let prog_names = prog_names.split('|');
@@ -354,8 +932,16 @@ pub async fn run(ctx: &Context, msg: &Message) -> CommandResult {
         if idx > 20 {
             Err("memory abuse?")?;
         }
-        progs.push(load_program(prog, msg)?);
+        progs.push((prog.to_string(), load_program(ctx, prog, msg)?));
+    }
+    // only counted once every program of the pipe was found
+    for (_, stored) in progs.iter() {
+        stored.count_use()?;
     }
+    let progs = progs
+        .into_iter()
+        .map(|(reference, stored)| (reference, stored.language, stored.program))
+        .collect::<Vec<_>>();
 
-    make_exec(ctx, msg, args.rest(), &progs).await
+    make_exec(ctx, msg, args.rest(), &progs, dialect, limit).await
 }
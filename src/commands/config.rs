@@ -0,0 +1,346 @@
+//! Settings that server admins can change for their server
+
+use crate::prelude::*;
+use crate::ser::*;
+use oxidized::game::clock::TimeControl;
+use rusqlite::{params, Result};
+use std::str::FromStr;
+
+#[group]
+#[help_available]
+#[only_in(guilds)]
+#[required_permissions("ADMINISTRATOR")]
+#[prefix = "config"]
+#[commands(config_get, config_set)]
+pub struct Config;
+
+/// A setting of a server
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Key {
+    Prefix,
+    GameChannels,
+    BrainfuckChannels,
+    IgnoredChannels,
+    DisabledCommands,
+    GameTimeout,
+    Rating,
+    InactiveDays,
+    GlobalRating,
+    GameInput,
+}
+
+const KEYS: [Key; 10] = [
+    Key::Prefix,
+    Key::GameChannels,
+    Key::BrainfuckChannels,
+    Key::IgnoredChannels,
+    Key::DisabledCommands,
+    Key::GameTimeout,
+    Key::Rating,
+    Key::InactiveDays,
+    Key::GlobalRating,
+    Key::GameInput,
+];
+
+impl Key {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Prefix => "prefix",
+            Self::GameChannels => "game_channels",
+            Self::BrainfuckChannels => "brainfuck_channels",
+            Self::IgnoredChannels => "ignored_channels",
+            Self::DisabledCommands => "disabled_commands",
+            Self::GameTimeout => "game_timeout",
+            Self::Rating => "rating",
+            Self::InactiveDays => "inactive_days",
+            Self::GlobalRating => "global_rating",
+            Self::GameInput => "game_input",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::Prefix => "Prefix of the commands, in addition to the default one",
+            Self::GameChannels => "Channels where games can be played, anywhere if empty",
+            Self::BrainfuckChannels => {
+                "Channels where brainfuck programs can be run, channels named `bot` or `spam` if empty"
+            }
+            Self::IgnoredChannels => "Channels where the bot doesn't respond to commands",
+            Self::DisabledCommands => "Commands that can't be used on this server",
+            Self::GameTimeout => "Time control of games: seconds per move (`60`), minutes with an increment in seconds (`5+3`) or hours per move (`24h`)",
+            Self::Rating => "`elo` rates competitive games, `none` makes all games casual",
+            Self::InactiveDays => {
+                "Players that didn't play a rated game for this many days are hidden from the leaderboards"
+            }
+            Self::GlobalRating => {
+                "`on` counts rated games for the global leaderboards of all servers, `off` (the default) keeps them on this server"
+            }
+            Self::GameInput => {
+                "`buttons` lets players move with buttons and menus, `reactions` with reactions or typed moves"
+            }
+        }
+    }
+
+    /// checks a new value and brings it into the form it is stored in
+    fn normalize(&self, value: &str) -> Result<String, &'static str> {
+        match self {
+            Self::Prefix => match value.split_whitespace().count() {
+                1 => Ok(value.trim().into()),
+                _ => Err("The prefix has to be a single word."),
+            },
+            Self::GameChannels | Self::BrainfuckChannels | Self::IgnoredChannels => {
+                let channels = value
+                    .split_whitespace()
+                    .map(|channel| {
+                        let id = channel.trim_start_matches("<#").trim_end_matches('>');
+                        id.parse::<u64>()
+                            .ok()
+                            .filter(|&id| id != 0)
+                            .map(|id| format!("{}", id))
+                            .ok_or("Channels have to be mentioned like #general.")
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(channels.join(" "))
+            }
+            Self::DisabledCommands => {
+                let commands = resolve_commands(value)?;
+                let config = CONFIG_GROUP.options.commands;
+                if commands
+                    .iter()
+                    .any(|command| config.iter().any(|c| std::ptr::eq(*c, *command)))
+                {
+                    return Err("The config commands can't be disabled.");
+                }
+                let names = commands.iter().map(|c| c.options.names[0]);
+                Ok(names.collect::<Vec<_>>().join(" "))
+            }
+            Self::GameTimeout => Ok(value.parse::<TimeControl>()?.to_string()),
+            Self::Rating => match value.trim().to_lowercase().as_str() {
+                rating @ "elo" | rating @ "none" => Ok(rating.into()),
+                _ => Err("Unknown rating system, use `elo` or `none`."),
+            },
+            Self::InactiveDays => match value.trim().parse::<u32>() {
+                Ok(days) if days > 0 => Ok(format!("{}", days)),
+                _ => Err("The number of days has to be a positive number."),
+            },
+            Self::GlobalRating => match value.trim().to_lowercase().as_str() {
+                global @ "on" | global @ "off" => Ok(global.into()),
+                _ => Err("Use `on` or `off`."),
+            },
+            Self::GameInput => match value.trim().to_lowercase().as_str() {
+                input @ "buttons" | input @ "reactions" => Ok(input.into()),
+                _ => Err("Use `buttons` or `reactions`."),
+            },
+        }
+    }
+
+    /// how the stored value is shown to users
+    fn display(&self, value: &str) -> String {
+        match self {
+            Self::GameChannels | Self::BrainfuckChannels | Self::IgnoredChannels => value
+                .split_whitespace()
+                .map(|id| format!("<#{}>", id))
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => format!("`{}`", value),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.to_lowercase();
+        KEYS.iter()
+            .copied()
+            .find(|key| key.as_str() == text)
+            .ok_or("unknown setting, use `config get` to see all of them")
+    }
+}
+
+/// Finds the commands of a list of names. A name can be an alias and can start with the prefix
+/// of its group, like `bf delete`.
+fn resolve_commands(text: &str) -> Result<Vec<&'static Command>, &'static str> {
+    let groups = crate::command_groups();
+    let mut words = text.split_whitespace().peekable();
+    let mut commands = Vec::new();
+    while let Some(word) = words.next() {
+        let prefixed = groups
+            .iter()
+            .filter(|group| group.options.prefixes.contains(&word))
+            .find_map(|group| {
+                let name = words.peek()?;
+                group
+                    .options
+                    .commands
+                    .iter()
+                    .find(|c| c.options.names.contains(name))
+            });
+        let command = match prefixed {
+            Some(command) => {
+                words.next();
+                *command
+            }
+            None => crate::prelude::commands()
+                .into_iter()
+                .find(|c| c.options.names.contains(&word))
+                .ok_or("Unknown command.")?,
+        };
+        commands.push(command);
+    }
+    Ok(commands)
+}
+
+fn create_table() -> Result<()> {
+    db()?.execute(
+        "CREATE TABLE IF NOT EXISTS server_config (server TEXT, key TEXT, value TEXT);",
+        params!(),
+    )?;
+    Ok(())
+}
+
+fn get(server: u64, key: Key) -> Result<Option<String>> {
+    create_table()?;
+    let value = db()?
+        .query_row(
+            "SELECT value FROM server_config WHERE server = ?1 AND key = ?2",
+            params!(format!("{}", server), key.as_str()),
+            |row| row.get::<_, String>(0),
+        )
+        .ok()
+        .filter(|value| !value.is_empty());
+    Ok(value)
+}
+
+fn set(server: u64, key: Key, value: &str) -> Result<()> {
+    create_table()?;
+    let server = format!("{}", server);
+    let db = db()?;
+
+    let affected = db.execute(
+        "UPDATE server_config SET value = ?3 WHERE server = ?1 AND key = ?2;",
+        params!(server, key.as_str(), value),
+    )?;
+
+    if affected == 0 {
+        db.execute(
+            "INSERT INTO server_config (server, key, value) VALUES (?1, ?2, ?3);",
+            params!(server, key.as_str(), value),
+        )?;
+    }
+    Ok(())
+}
+
+/// The prefix a server chose
+pub fn prefix(server: u64) -> Result<Option<String>> {
+    get(server, Key::Prefix)
+}
+
+/// The channels stored in a setting, empty if it isn't set
+pub fn channels(server: u64, key: Key) -> Result<Vec<ChannelId>> {
+    let channels = get(server, key)?
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter_map(|id| id.parse().ok())
+        .map(ChannelId::new)
+        .collect();
+    Ok(channels)
+}
+
+/// The main names of the disabled commands, which the framework passes to hooks
+pub fn disabled_commands(server: u64) -> Result<Vec<&'static str>> {
+    let commands = get(server, Key::DisabledCommands)?.unwrap_or_default();
+    // older settings can contain aliases, names that are unknown by now are skipped
+    let commands = commands
+        .split_whitespace()
+        .filter_map(|name| resolve_commands(name).ok())
+        .flatten()
+        .map(|c| c.options.names[0])
+        .collect();
+    Ok(commands)
+}
+
+/// The time control of games, if the server changed it
+pub fn game_timeout(server: u64) -> Result<Option<TimeControl>> {
+    Ok(get(server, Key::GameTimeout)?.and_then(|control| control.parse().ok()))
+}
+
+/// Whether competitive games change the rating of the players
+pub fn rated(server: u64) -> Result<bool> {
+    Ok(get(server, Key::Rating)?.as_deref() != Some("none"))
+}
+
+/// After how many days without a rated game players are hidden from the leaderboards
+pub fn inactive_days(server: u64) -> Result<Option<u32>> {
+    Ok(get(server, Key::InactiveDays)?.and_then(|days| days.parse().ok()))
+}
+
+/// Whether rated games of the server count for the global ratings, servers have to opt in
+pub fn global_rating(server: u64) -> Result<bool> {
+    Ok(get(server, Key::GlobalRating)?.as_deref() == Some("on"))
+}
+
+/// Whether players make their moves with reactions instead of buttons and menus
+pub fn reaction_input(server: u64) -> Result<bool> {
+    Ok(get(server, Key::GameInput)?.as_deref() == Some("reactions"))
+}
+
+#[command("get")]
+#[description = "Shows the settings of this server."]
+#[usage = "[setting]"]
+#[example = "prefix"]
+async fn config_get(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = msg.guild_id.ok_or("not sent in a guild")?.get();
+
+    let keys = match msg.args().single::<String>() {
+        Ok(key) => vec![key.parse::<Key>()?],
+        Err(_) => KEYS.to_vec(),
+    };
+
+    let mut fields = Vec::new();
+    for key in keys {
+        let value = match get(server, key)? {
+            Some(value) => key.display(&value),
+            None => String::from("*not set*"),
+        };
+        let value = format!("{}\n{}", value, key.description());
+        fields.push((key.as_str(), value, false));
+    }
+
+    msg.ereply(ctx, |e| e.title("Server Settings").fields(fields))
+        .await?;
+    Ok(())
+}
+
+#[command("set")]
+#[min_args(1)]
+#[description = "Changes a setting of this server, leave out the value to reset it. Lists of channels or commands are separated by spaces."]
+#[usage = "<setting> [value]"]
+#[example = "game_channels #games #bot-spam"]
+async fn config_set(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = msg.guild_id.ok_or("not sent in a guild")?.get();
+
+    let mut args = msg.args();
+    let key = args.single::<String>()?.parse::<Key>()?;
+    let value = match args.rest() {
+        "" => String::new(),
+        value => match key.normalize(value) {
+            Ok(value) => value,
+            Err(err) => return msg.err_reply(ctx, err).await,
+        },
+    };
+
+    set(server, key, &value)?;
+
+    let value = match value.as_str() {
+        "" => String::from("*not set*"),
+        value => key.display(value),
+    };
+    msg.ereply(ctx, |e| {
+        e.title("Server Settings")
+            .description(format!("`{}` is now {}.", key.as_str(), value))
+    })
+    .await?;
+    Ok(())
+}
@@ -30,7 +30,7 @@ async fn solution(ctx: &Context, msg: &Message) -> CommandResult {
     use rand::*;
     use rand_pcg::*;
 
-    let mut rng = Pcg64::seed_from_u64(*msg.author.id.as_u64());
+    let mut rng = Pcg64::seed_from_u64(msg.author.id.get());
 
     let answer_pool;
 
@@ -1,5 +1,19 @@
+use super::config::{self, Key};
 use crate::ser::*;
 
+/// Whether the message was sent in one of the channels of a setting.
+///
+/// `None` if the server didn't set any channels.
+fn in_configured_channel(msg: &Message, key: Key) -> Option<bool> {
+    let server = msg.guild_id?.get();
+    let channels = config::channels(server, key).ok()?;
+    if channels.is_empty() {
+        None
+    } else {
+        Some(channels.contains(&msg.channel_id))
+    }
+}
+
 #[check]
 #[name = "Spam"]
 pub async fn only_in_spam(
@@ -8,6 +22,14 @@ pub async fn only_in_spam(
     _: &mut Args,
     _: &CommandOptions,
 ) -> Result<(), Reason> {
+    if let Some(allowed) = in_configured_channel(msg, Key::BrainfuckChannels) {
+        return if allowed {
+            Ok(())
+        } else {
+            Err(Reason::Unknown)
+        };
+    }
+
     let channel = msg
         .channel_id
         .to_channel(ctx)
@@ -22,3 +44,17 @@ pub async fn only_in_spam(
     }
     Ok(())
 }
+
+#[check]
+#[name = "GameChannel"]
+pub async fn only_in_game_channels(
+    _: &Context,
+    msg: &Message,
+    _: &mut Args,
+    _: &CommandOptions,
+) -> Result<(), Reason> {
+    match in_configured_channel(msg, Key::GameChannels) {
+        Some(false) => Err(Reason::Unknown),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,439 @@
+//! Brainfuck code golf challenges, solutions are ranked by their length
+
+use super::brainfuck::code_block;
+use crate::ser::*;
+use crate::worker::run_job;
+use crate::{prelude::*, tryc};
+use chrono::prelude::*;
+use oxidized::brainfuck::*;
+use oxidized::esolang::*;
+use rusqlite::{params, Result};
+
+/// How many steps a solution may make on a single test case. Unlike a time limit,
+/// this doesn't depend on how busy the bot is.
+const STEP_BUDGET: usize = 10_000_000;
+
+/// How many steps the solutions of a challenge may make together when they are judged
+/// on a new test case
+const REJUDGE_BUDGET: usize = 200_000_000;
+
+/// How many test cases a challenge can have
+const MAX_TESTCASES: usize = 20;
+
+/// How many solutions the leaderboard lists
+const LISTED_SOLUTIONS: usize = 15;
+
+fn create_tables() -> Result<()> {
+    let db = db()?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS bf_challenges (server TEXT, name TEXT, task TEXT);",
+        params!(),
+    )?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS bf_testcases (server TEXT, challenge TEXT, input BLOB, output BLOB);",
+        params!(),
+    )?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS bf_solutions (server TEXT, challenge TEXT, author TEXT, program TEXT, length INTEGER, steps INTEGER, time INTEGER);",
+        params!(),
+    )?;
+    Ok(())
+}
+
+fn challenge_task(server: &str, name: &str) -> CommandResult<String> {
+    let task = db()?
+        .query_row(
+            "SELECT task FROM bf_challenges WHERE server = ?1 AND name = ?2",
+            params!(server, name),
+            |row| row.get(0),
+        )
+        .map_err(|_| format!("There is no challenge `{}`.", name))?;
+    Ok(task)
+}
+
+fn testcases(server: &str, challenge: &str) -> Result<Vec<TestCase>> {
+    let db = db()?;
+    let mut stmt =
+        db.prepare("SELECT input, output FROM bf_testcases WHERE server = ?1 AND challenge = ?2")?;
+    let cases = stmt
+        .query_map(params!(server, challenge), |row| {
+            Ok(TestCase {
+                input: row.get(0)?,
+                output: row.get(1)?,
+            })
+        })?
+        .collect();
+    cases
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[min_args(2)]
+#[description = "Posts a code golf challenge or changes its task. Add test cases with `testcase`."]
+#[usage = "<name> <task>"]
+#[example = "reverse Write the input in reverse."]
+pub async fn challenge(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = format!("{}", msg.guild_id.ok_or("not sent in a guild")?);
+    let mut args = msg.args();
+    let name = args.single::<String>()?;
+    let task = args.rest().trim();
+
+    if name.chars().any(|ch| !ch.is_ascii_alphanumeric()) {
+        Err("Invalid challenge name")?;
+    }
+
+    create_tables()?;
+    let db = db()?;
+    let affected = db.execute(
+        "UPDATE bf_challenges SET task = ?3 WHERE server = ?1 AND name = ?2",
+        params!(server, name, task),
+    )?;
+    if affected == 0 {
+        db.execute(
+            "INSERT INTO bf_challenges (server, name, task) VALUES (?1, ?2, ?3)",
+            params!(server, name, task),
+        )?;
+    }
+
+    msg.ereply(ctx, |e| {
+        e.title(format!("Code Golf Challenge `{}`", name))
+            .description(task)
+    })
+    .await?;
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[min_args(1)]
+#[description = "Adds a hidden test case to a challenge, the message is deleted so nobody sees it. Input and output are separated by `=>`, `\\n`, `\\t`, `\\0`, `\\\\` and `\\xHH` stand for the bytes they escape. Solutions that fail the new test case are removed."]
+#[usage = "<challenge> <input> => <output>"]
+#[example = "reverse Hello\\n => \\nolleH"]
+pub async fn testcase(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = format!("{}", msg.guild_id.ok_or("not sent in a guild")?);
+    let mut args = msg.args();
+    let challenge = args.single::<String>()?;
+    let rest = args.rest();
+
+    // the test case stays hidden, even if it can't be added
+    msg.delete(ctx).await.ok();
+
+    let (input, output) = match rest.split_once(" => ") {
+        Some((input, output)) => (unescape(input), unescape(output)),
+        None => {
+            return msg
+                .err_reply(ctx, "Separate input and output by `=>`.")
+                .await
+        }
+    };
+    let (input, output) = match (input, output) {
+        (Ok(input), Ok(output)) => (input, output),
+        (Err(why), _) | (_, Err(why)) => return msg.err_reply(ctx, &why).await,
+    };
+
+    create_tables()?;
+    if let Err(why) = challenge_task(&server, &challenge) {
+        return msg.err_reply(ctx, &why.to_string()).await;
+    }
+    let cases = testcases(&server, &challenge)?.len();
+    if cases >= MAX_TESTCASES {
+        return msg
+            .err_reply(ctx, "This challenge has enough test cases.")
+            .await;
+    }
+
+    // the test case is only added once every solution was judged on it
+    let case = TestCase { input, output };
+    let verdicts = match rejudge(ctx, msg, &server, &challenge, case.clone()).await? {
+        Some(verdicts) => verdicts,
+        None => {
+            return msg
+                .err_reply(
+                    ctx,
+                    "The solutions couldn't be judged on the test case, so it wasn't added.",
+                )
+                .await
+        }
+    };
+
+    let db = db()?;
+    db.execute(
+        "INSERT INTO bf_testcases (server, challenge, input, output) VALUES (?1, ?2, ?3, ?4)",
+        params!(server, challenge, case.input, case.output),
+    )?;
+    let mut removed = 0;
+    for (author, steps) in verdicts {
+        match steps {
+            Some(steps) => db.execute(
+                "UPDATE bf_solutions SET steps = steps + ?4 WHERE server = ?1 AND challenge = ?2 AND author = ?3",
+                params!(server, challenge, author, steps),
+            )?,
+            None => {
+                removed += 1;
+                db.execute(
+                    "DELETE FROM bf_solutions WHERE server = ?1 AND challenge = ?2 AND author = ?3",
+                    params!(server, challenge, author),
+                )?
+            }
+        };
+    }
+
+    let mut description = format!("Test case {} was added.", cases + 1);
+    if removed > 0 {
+        description += &format!(" {} solutions failed it and were removed.", removed);
+    }
+    msg.ereply(ctx, |e| {
+        e.title(format!("Code Golf Challenge `{}`", challenge))
+            .description(description)
+    })
+    .await?;
+    Ok(())
+}
+
+/// Judges the solutions of a challenge on a new test case, they passed the other ones already.
+/// Gives the steps each solution made on it, `None` for the ones that failed. `None` if the
+/// judgement didn't finish, because it was cancelled or the solutions used up `REJUDGE_BUDGET`.
+async fn rejudge(
+    ctx: &Context,
+    msg: &Message,
+    server: &str,
+    challenge: &str,
+    case: TestCase,
+) -> CommandResult<Option<Vec<(String, Option<i64>)>>> {
+    let solutions = {
+        let db = db()?;
+        let mut stmt = db.prepare(
+            "SELECT author, program FROM bf_solutions WHERE server = ?1 AND challenge = ?2",
+        )?;
+        let rows = stmt.query_map(params!(server, challenge), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+    if solutions.is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+
+    let verdicts = run_job(ctx, msg, move |cancel| {
+        let cases = [case];
+        let mut spent = 0;
+        let mut verdicts = Vec::new();
+        for (author, program) in solutions {
+            let instructions = match parse_instructions(&program) {
+                Ok(instructions) => instructions,
+                Err(_) => {
+                    verdicts.push((author, None));
+                    continue;
+                }
+            };
+            let budget = STEP_BUDGET.min(REJUDGE_BUDGET - spent);
+            let steps = match judge(&instructions, &cases, Limit::Steps(budget), cancel) {
+                Verdict::Passed { steps } => {
+                    spent += steps;
+                    Some(steps as i64)
+                }
+                Verdict::Failed { execution, .. } => {
+                    spent += execution.steps;
+                    // a solution that wasn't judged to the end isn't removed
+                    match execution.exit_code {
+                        ExitCode::Cancelled | ExitCode::Timeout => return None,
+                        ExitCode::OutOfSteps if budget < STEP_BUDGET => return None,
+                        _ => None,
+                    }
+                }
+            };
+            verdicts.push((author, steps));
+        }
+        Some(verdicts)
+    })
+    .await?;
+    Ok(verdicts.flatten())
+}
+
+#[command]
+#[only_in(guilds)]
+#[max_args(0)]
+#[description = "Lists the code golf challenges of this server."]
+pub async fn challenges(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = format!("{}", msg.guild_id.ok_or("not sent in a guild")?);
+
+    create_tables()?;
+    let lines = {
+        let db = db()?;
+        let mut stmt = db.prepare(
+            "SELECT name, task, (SELECT COUNT(DISTINCT author) FROM bf_solutions s WHERE s.server = c.server AND s.challenge = c.name)
+            FROM bf_challenges c WHERE server = ?1 ORDER BY name",
+        )?;
+        let rows = stmt.query_map(params!(server), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut lines = Vec::new();
+        for row in rows {
+            let (name, task, solvers) = tryc!(row.ok());
+            lines.push(format!("`{}` ({} solved) {}\n", name, solvers, task));
+        }
+        lines
+    };
+    let lines = split_into_fields(&lines, "There are no challenges yet.");
+
+    msg.ereply(ctx, |e| {
+        e.title("Code Golf Challenges")
+            .description(lines[0].clone())
+    })
+    .await?;
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[min_args(2)]
+#[description = "Submits a solution to a code golf challenge. It has to pass every test case within a budget of 10000000 steps. The message is deleted, so nobody can copy the solution."]
+#[usage = "<challenge> <program>"]
+#[example = "reverse >,[>,]<[.<]"]
+#[bucket("brainfuck")]
+pub async fn submit(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = format!("{}", msg.guild_id.ok_or("not sent in a guild")?);
+    let mut args = msg.args();
+    let challenge = args.single::<String>()?;
+    let program = code_block(args.rest()).trim().to_string();
+
+    msg.delete(ctx).await.ok();
+
+    create_tables()?;
+    if let Err(why) = challenge_task(&server, &challenge) {
+        return msg.err_reply(ctx, &why.to_string()).await;
+    }
+    let cases = testcases(&server, &challenge)?;
+    if cases.is_empty() {
+        return msg
+            .err_reply(ctx, "This challenge has no test cases yet.")
+            .await;
+    }
+    let instructions = match parse_instructions(&program) {
+        Ok(instructions) => instructions,
+        Err(why) => return msg.err_reply(ctx, &why.to_string()).await,
+    };
+
+    let verdict = run_job(ctx, msg, move |cancel| {
+        judge(&instructions, &cases, Limit::Steps(STEP_BUDGET), cancel)
+    })
+    .await?;
+    let verdict = match verdict {
+        Some(verdict) => verdict,
+        None => return Ok(()),
+    };
+    let steps = match verdict {
+        Verdict::Passed { steps } => steps,
+        Verdict::Failed { case, execution } => {
+            let reason = match execution.exit_code {
+                ExitCode::Success => String::from("wrong output"),
+                exit_code => format!("{:?}", exit_code),
+            };
+            let error = format!(
+                "{}, your solution failed test case {}: {}",
+                msg.author.mention(),
+                case + 1,
+                reason
+            );
+            return msg.err_reply(ctx, &error).await;
+        }
+    };
+
+    let author = format!("{}", msg.author.id);
+    let length = program.len() as i64;
+    let steps = steps as i64;
+    let best = db()?
+        .query_row(
+            "SELECT length, steps FROM bf_solutions WHERE server = ?1 AND challenge = ?2 AND author = ?3",
+            params!(server, challenge, author),
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+        )
+        .ok();
+    let improved = best.is_none_or(|best| (length, steps) < best);
+    if improved {
+        let db = db()?;
+        db.execute(
+            "DELETE FROM bf_solutions WHERE server = ?1 AND challenge = ?2 AND author = ?3",
+            params!(server, challenge, author),
+        )?;
+        db.execute(
+            "INSERT INTO bf_solutions (server, challenge, author, program, length, steps, time) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params!(server, challenge, author, program, length, steps, Utc::now().timestamp()),
+        )?;
+    }
+
+    let mut description = format!(
+        "{} passed all test cases with {} bytes in {} steps.",
+        msg.author.mention(),
+        length,
+        steps
+    );
+    if !improved {
+        description += " Your earlier solution is better.";
+    }
+    msg.ereply(ctx, |e| {
+        e.title(format!("Code Golf Challenge `{}`", challenge))
+            .description(description)
+    })
+    .await?;
+    Ok(())
+}
+
+#[command]
+#[only_in(guilds)]
+#[min_args(1)]
+#[max_args(1)]
+#[description = "Shows the task of a code golf challenge and its best solutions, the shortest first and the faster one if two are equally long."]
+#[usage = "<challenge>"]
+#[example = "reverse"]
+pub async fn golf(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = format!("{}", msg.guild_id.ok_or("not sent in a guild")?);
+    let challenge = msg.args().single::<String>()?;
+
+    create_tables()?;
+    let task = challenge_task(&server, &challenge)?;
+    let lines = {
+        let db = db()?;
+        let mut stmt = db.prepare(
+            "SELECT author, length, steps FROM bf_solutions WHERE server = ?1 AND challenge = ?2
+            ORDER BY length, steps, time LIMIT ?3",
+        )?;
+        let rows = stmt.query_map(params!(server, challenge, LISTED_SOLUTIONS as i64), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut lines = Vec::new();
+        for (idx, row) in rows.enumerate() {
+            let (author, length, steps) = tryc!(row.ok());
+            let author = UserId::new(tryc!(author.parse().ok()));
+            lines.push(format!(
+                "`{:>2}. {:>4} bytes {:>8} steps` {}\n",
+                idx + 1,
+                length,
+                steps,
+                author.mention()
+            ));
+        }
+        lines
+    };
+    let lines = split_into_fields(&lines, "Nobody solved this challenge yet.");
+
+    msg.ereply(ctx, |e| {
+        e.title(format!("Code Golf Challenge `{}`", challenge))
+            .description(task)
+            .field("Leaderboard", lines[0].clone(), false)
+    })
+    .await?;
+    Ok(())
+}
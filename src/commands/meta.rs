@@ -10,8 +10,8 @@ async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
     let elapsed = before_ping.elapsed().as_millis();
 
     ping.eedit(ctx, |e| {
-        e.title("Ping Stats");
-        e.field("Roundtrip", format!("`{}ms`", elapsed), false)
+        e.title("Ping Stats")
+            .field("Roundtrip", format!("`{}ms`", elapsed), false)
     })
     .await?;
 
@@ -22,25 +22,25 @@ async fn ping(ctx: &Context, msg: &Message) -> CommandResult {
 #[aliases(about)]
 async fn info(ctx: &Context, msg: &Message) -> CommandResult {
     msg.ereply(ctx, |e| {
-        e.title(format!("{} info page", NAME));
-        e.description(format!(
-            "Information about the bot itself. Use `{}help` to get a list of commands.",
-            *PREFIX
-        ));
-        e.field(
-            "Author",
-            format!(" {} | [GitHub](https://github.com/JM4ier)", DISCORD_AUTHOR),
-            false,
-        );
-        e.field("Version", format!("{} v{}", NAME, VERSION), false);
-        e.field(
-            "Source",
-            "[Repository](https://github.com/JM4ier/oxidized)",
-            false,
-        );
-        e.field("Build Time", format!("`{}`", BUILD_DATE), false);
-        e.field("Start Time", format!("`{}`", *START_DATE), false);
-        e.field("System", format!("`{}`", *SYSTEM_NAME), false)
+        e.title(format!("{} info page", NAME))
+            .description(format!(
+                "Information about the bot itself. Use `{}help` to get a list of commands.",
+                *PREFIX
+            ))
+            .field(
+                "Author",
+                format!(" {} | [GitHub](https://github.com/JM4ier)", DISCORD_AUTHOR),
+                false,
+            )
+            .field("Version", format!("{} v{}", NAME, VERSION), false)
+            .field(
+                "Source",
+                "[Repository](https://github.com/JM4ier/oxidized)",
+                false,
+            )
+            .field("Build Time", format!("`{}`", BUILD_DATE), false)
+            .field("Start Time", format!("`{}`", *START_DATE), false)
+            .field("System", format!("`{}`", *SYSTEM_NAME), false)
     })
     .await?;
     Ok(())
@@ -51,12 +51,7 @@ async fn info(ctx: &Context, msg: &Message) -> CommandResult {
 #[example = r#"The bot doesn't respond with "nice" when writing 69."#]
 async fn bug(_: &Context, msg: &Message) -> CommandResult {
     let bug = msg.args().rest().replace("\n", "\\n");
-    let author = format!(
-        "{}#{}({})",
-        msg.author.name,
-        msg.author.discriminator,
-        msg.author.id.as_u64()
-    );
+    let author = format!("{}({})", msg.author.tag(), msg.author.id.get());
     let bug_txt = format!("{}: {}\n", author, bug);
 
     let mut file = OpenOptions::new()
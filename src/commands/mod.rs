@@ -1,7 +1,10 @@
 pub mod brainfuck;
+pub mod config;
 pub mod easteregg;
 pub mod filter;
+pub mod golf;
 pub mod meta;
 pub mod owner;
 pub mod play;
+pub mod slash;
 pub mod util;
@@ -21,15 +21,15 @@ async fn quit(ctx: &Context, msg: &Message) -> CommandResult {
 
     if let Some(manager) = data.get::<ShardManagerContainer>() {
         msg.ereply(ctx, |e| {
-            e.title("Shutting down!");
-            e.description("Hopefully going alright")
+            e.title("Shutting down!")
+                .description("Hopefully going alright")
         })
         .await?;
-        manager.lock().await.shutdown_all().await;
+        manager.shutdown_all().await;
     } else {
         msg.ereply(ctx, |e| {
-            e.title("Error");
-            e.description("There was a problem getting the shard manager")
+            e.title("Error")
+                .description("There was a problem getting the shard manager")
         })
         .await?;
         return Ok(());
@@ -53,7 +53,7 @@ async fn repeat(ctx: &Context, msg: &Message) -> CommandResult {
     let count = args.single::<u32>()?;
     let word = args.rest();
     for _ in 0..count {
-        msg.channel_id.say(&ctx.http, &word).await?;
+        msg.channel_id.say(&ctx.http, word).await?;
 
         if initial_spam != STOP_SPAM.load(Ordering::SeqCst) {
             break;
@@ -74,17 +74,15 @@ async fn stop_spam(_: &Context, _: &Message) -> CommandResult {
 #[example = ""]
 #[example = "25"]
 async fn delete(ctx: &Context, msg: &Message) -> CommandResult {
-    let delete_count = msg.args().single::<u64>().unwrap_or(100);
+    let delete_count = msg.args().single::<u8>().unwrap_or(100);
     let channel = msg.channel_id;
 
     let messages = channel
-        .messages(ctx, |retriever| {
-            retriever.before(msg.id).limit(delete_count)
-        })
+        .messages(ctx, GetMessages::new().before(msg.id).limit(delete_count))
         .await?;
 
     for msg in messages.into_iter() {
-        if msg.is_own(ctx).await {
+        if msg.is_own(ctx) {
             channel.delete_message(ctx, msg.id).await?;
         }
     }
@@ -109,12 +107,12 @@ async fn debug(_: &Context, msg: &Message) -> CommandResult {
 async fn status(ctx: &Context, msg: &Message) -> CommandResult {
     let mut args = msg.args();
     let activity = match args.single::<String>()?.as_str() {
-        "playing" => Activity::playing(args.rest()),
-        "listening" => Activity::listening(args.rest()),
-        "competing" => Activity::competing(args.rest()),
+        "playing" => ActivityData::playing(args.rest()),
+        "listening" => ActivityData::listening(args.rest()),
+        "competing" => ActivityData::competing(args.rest()),
         "streaming" => {
             let url = args.single::<String>()?;
-            Activity::streaming(args.rest(), &url)
+            ActivityData::streaming(args.rest(), url.as_str())?
         }
         _ => return Err(From::from("invalid activity type")),
     };
@@ -130,11 +128,10 @@ async fn status(ctx: &Context, msg: &Message) -> CommandResult {
 async fn nick(ctx: &Context, msg: &Message) -> CommandResult {
     let nick = msg.args().single::<String>().ok();
     let nick = nick.as_ref().map(String::as_str);
-    let guild = *msg
+    let guild = msg
         .guild_id
-        .ok_or("This message was not send in a guild.")?
-        .as_u64();
-    ctx.http.edit_nickname(guild, nick).await?;
+        .ok_or("This message was not send in a guild.")?;
+    ctx.http.edit_nickname(guild, nick, None).await?;
     Ok(())
 }
 
@@ -165,7 +162,7 @@ async fn delet(ctx: &Context, msg: &Message) -> CommandResult {
 
     let around_msgs = msg
         .channel_id
-        .messages(ctx, |r| r.around(to_delet).limit(1))
+        .messages(ctx, GetMessages::new().around(to_delet).limit(1))
         .await?;
 
     for msg in around_msgs {
@@ -0,0 +1,207 @@
+use super::*;
+use oxidized::game::analysis;
+
+#[group]
+#[help_available]
+#[only_in(guilds)]
+#[commands(analyze, openings)]
+pub struct Analysis;
+
+/// How many openings are listed
+const LISTED_OPENINGS: usize = 10;
+
+/// A game from the game logs
+struct LoggedGame {
+    players: [UserId; 2],
+    moves: Vec<u8>,
+    winner: Option<usize>,
+}
+
+fn get_game(server: u64, game: &str, id: i64) -> CommandResult<LoggedGame> {
+    let (player1, player2, moves, result) = db()?
+        .query_row(
+            &format!(
+                "SELECT player1, player2, moves, result FROM {} WHERE rowid = ?1 AND server = ?2",
+                games_table(game)
+            ),
+            params!(id, format!("{}", server)),
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                    row.get::<_, u8>(3)?,
+                ))
+            },
+        )
+        .map_err(|_| "There is no such game on this server.")?;
+    Ok(LoggedGame {
+        players: [UserId::new(player1.parse()?), UserId::new(player2.parse()?)],
+        moves,
+        winner: result.checked_sub(1).map(usize::from),
+    })
+}
+
+/// Reviews every move of a logged game, together with its description
+pub fn review<T: Move, G: PvpGame<T> + Clone>(
+    start: G,
+    moves: &[u8],
+) -> CommandResult<Vec<(String, Review)>> {
+    let moves = decode_moves::<T>(moves).ok_or("broken game log")?;
+    let mut ai = G::analysis_ai().ok_or("There is no AI that can analyze this game.")?;
+    let reviews =
+        analysis::analyze(&start, &moves, &mut *ai).ok_or("This game can't be analyzed.")?;
+
+    let mut game = start;
+    let mut described = Vec::new();
+    for (idx, (play, review)) in moves.into_iter().zip(reviews).enumerate() {
+        described.push((game.describe_move(&play), review));
+        game.make_move(play, idx % 2);
+    }
+    Ok(described)
+}
+
+/// The openings of logged games, together with a description of their first move
+pub fn describe_openings<T: Move, G: PvpGame<T>>(
+    start: G,
+    games: &[(Vec<u8>, Option<usize>)],
+) -> Vec<(String, Opening)> {
+    analysis::openings(games, T::SIZE)
+        .into_iter()
+        .filter_map(|opening| {
+            let first_move = T::decode(&opening.first_move)?;
+            Some((start.describe_move(&first_move), opening))
+        })
+        .collect()
+}
+
+#[command]
+#[min_args(2)]
+#[description = "Replays a rated game and lets the AI rate every move. Moves that make the chance to win drop a lot are marked as blunders. The ids of the games are listed in the rating history."]
+#[usage = "<game> <id>"]
+#[example = "connect4 42"]
+async fn analyze(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = msg.guild_id.ok_or("not sent in a guild")?.get();
+
+    let mut args = msg.args();
+    let game = args.single::<String>()?;
+    let game = game_names()
+        .into_iter()
+        .find(|&name| name == game)
+        .ok_or("unknown game")?;
+    let id = args.single::<i64>()?;
+
+    create_tables(game)?;
+    let logged = match get_game(server, game, id) {
+        Ok(logged) => logged,
+        Err(why) => return msg.err_reply(ctx, &why.to_string()).await,
+    };
+
+    let typing = msg.channel_id.start_typing(&ctx.http);
+    // the AI takes a while for every position
+    let moves = logged.moves.clone();
+    let reviews = tokio::task::spawn_blocking(move || review_game(game, &moves)).await?;
+    typing.stop();
+    let reviews = match reviews {
+        Ok(reviews) => reviews,
+        Err(why) => return msg.err_reply(ctx, &why.to_string()).await,
+    };
+
+    let mentions = [logged.players[0].mention(), logged.players[1].mention()];
+    let lines = reviews
+        .iter()
+        .enumerate()
+        .map(|(idx, (description, review))| {
+            format!(
+                "`{:>2}. {:>3.0}% → {:>3.0}%` {} {}{}\n",
+                idx + 1,
+                review.before * 100.0,
+                review.after * 100.0,
+                mentions[review.player],
+                description,
+                if review.is_blunder() { " **??**" } else { "" }
+            )
+        })
+        .collect::<Vec<_>>();
+    let fields = split_into_fields(&lines, "No moves were made.");
+
+    let result = match logged.winner {
+        Some(winner) => format!("{} won.", mentions[winner]),
+        None => String::from("The game ended in a draw."),
+    };
+    let blunders = reviews.iter().filter(|(_, r)| r.is_blunder()).count();
+    let description = format!(
+        "{} vs {}\n{}\n\nThe chance to win of the player to move, before and after each move. Blunders (`??`): {}",
+        mentions[0], mentions[1], result, blunders
+    );
+
+    msg.ereply(ctx, |e| {
+        e.title(format!("Analysis of {} #{}", game, id))
+            .description(description)
+            .fields(
+                fields
+                    .into_iter()
+                    .take(5)
+                    .map(|field| ("Moves", field, false)),
+            )
+    })
+    .await?;
+    Ok(())
+}
+
+#[command]
+#[min_args(1)]
+#[description = "Shows the most common first moves of the rated games on this server and how well the player that made them scored."]
+#[usage = "<game>"]
+#[example = "connect4"]
+async fn openings(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = format!("{}", msg.guild_id.ok_or("not sent in a guild")?);
+
+    let game = msg.args().single::<String>()?;
+    let game = game_names()
+        .into_iter()
+        .find(|&name| name == game)
+        .ok_or("unknown game")?;
+
+    create_tables(game)?;
+    let games = {
+        let db = db()?;
+        let mut stmt = db.prepare(&format!(
+            "SELECT moves, result FROM {} WHERE server = ?1",
+            games_table(game)
+        ))?;
+        let rows = stmt.query_map(params!(server), |row| {
+            Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, u8>(1)?))
+        })?;
+
+        let mut games = Vec::new();
+        for row in rows {
+            let (moves, result) = tryc!(row.ok());
+            games.push((moves, result.checked_sub(1).map(usize::from)));
+        }
+        games
+    };
+
+    let lines = game_openings(game, &games)?
+        .into_iter()
+        .take(LISTED_OPENINGS)
+        .map(|(description, opening)| {
+            format!(
+                "`{:>4} games {:>3.0}%` {}\n",
+                opening.games,
+                opening.score() * 100.0,
+                description
+            )
+        })
+        .collect::<Vec<_>>();
+    let lines = split_into_fields(&lines, "No rated games were played yet.");
+
+    msg.ereply(ctx, |e| {
+        e.title(format!("{} Openings", game)).description(format!(
+            "Number of games and points per game of the first player.\n\n{}",
+            lines[0]
+        ))
+    })
+    .await?;
+    Ok(())
+}
@@ -1,8 +1,21 @@
 use super::*;
+use chrono::prelude::*;
+use oxidized::game::elo::*;
 
-pub type Elo = f64;
+/// The server id of the rating pool that all servers share
+pub const GLOBAL: u64 = 0;
 
-fn get(server: u64, player: u64, game: &str) -> Result<Elo> {
+/// A rating change of a player caused by a game
+pub struct Change {
+    /// row of the game in the game logs
+    pub game: i64,
+    pub opponent: UserId,
+    pub before: Elo,
+    pub after: Elo,
+    pub time: DateTime<Utc>,
+}
+
+pub fn get(server: u64, player: u64, game: &str) -> Result<Elo> {
     let player = format!("{}", player);
     let server = format!("{}", server);
     let db = db()?;
@@ -15,7 +28,7 @@ fn get(server: u64, player: u64, game: &str) -> Result<Elo> {
             params!(server, player),
             |row| row.get(0),
         )
-        .unwrap_or(1200.0);
+        .unwrap_or(DEFAULT_ELO);
     Ok(elo)
 }
 
@@ -44,31 +57,133 @@ fn set(server: u64, player: u64, game: &str, elo: Elo) -> Result<()> {
     Ok(())
 }
 
+/// Stores the rating change of a player, `game` is the row of the game in the game logs
+fn add_change(
+    server: u64,
+    player: u64,
+    opponent: u64,
+    game_name: &str,
+    game: i64,
+    elo: [Elo; 2],
+) -> Result<()> {
+    db()?.execute(
+        &format!(
+            "INSERT INTO {} (server, player, opponent, game, before, after, time) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7);",
+            history_table(game_name)
+        ),
+        params!(
+            format!("{}", server),
+            format!("{}", player),
+            format!("{}", opponent),
+            game,
+            elo[0],
+            elo[1],
+            Utc::now().timestamp(),
+        ),
+    )?;
+    Ok(())
+}
+
+/// All rating changes of a player, the oldest first
+pub fn history(server: u64, player: u64, game_name: &str) -> CommandResult<Vec<Change>> {
+    let db = db()?;
+    let mut stmt = db.prepare(&format!(
+        "SELECT opponent, before, after, time, game FROM {} WHERE server = ?1 AND player = ?2 ORDER BY time, game",
+        history_table(game_name)
+    ))?;
+    let rows = stmt.query_map(
+        params!(format!("{}", server), format!("{}", player)),
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        },
+    )?;
+
+    let mut changes = Vec::new();
+    for row in rows {
+        let (opponent, before, after, time, game) = row?;
+        changes.push(Change {
+            game,
+            opponent: UserId::new(opponent.parse()?),
+            before,
+            after,
+            time: Utc.timestamp_opt(time, 0).single().ok_or("invalid time")?,
+        });
+    }
+    Ok(changes)
+}
+
+/// Players of a server that didn't play a rated game for the given number of days.
+/// Players without any stored rating changes are never inactive.
+pub fn inactive_players(server: u64, game_name: &str, days: u32) -> Result<Vec<UserId>> {
+    let since = Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60;
+    let db = db()?;
+    let mut stmt = db.prepare(&format!(
+        "SELECT player FROM {} WHERE server = ?1 GROUP BY player HAVING MAX(time) < ?2",
+        history_table(game_name)
+    ))?;
+    let rows = stmt.query_map(params!(format!("{}", server), since), |row| {
+        row.get::<_, String>(0)
+    })?;
+
+    let mut players = Vec::new();
+    for player in rows {
+        let player = tryc!(player.ok().and_then(|p| p.parse::<u64>().ok()));
+        players.push(UserId::new(player));
+    }
+    Ok(players)
+}
+
+/// Rates a game, `game` is the row of the game in the game logs.
+/// If the server opted in, the game also counts for the global ratings.
 pub fn process_game(
     game_name: &str,
     server: u64,
     player_id: &[u64],
     winner: Option<usize>,
-) -> Result<()> {
-    let mut elo = Vec::new();
-    for &p in player_id.iter() {
-        elo.push(get(server, p, game_name)?);
+    game: i64,
+) -> CommandResult {
+    rate(game_name, server, player_id, winner, game)?;
+    if config::global_rating(server)? {
+        rate(game_name, GLOBAL, player_id, winner, game)?;
     }
+    Ok(())
+}
 
-    // expected score for player 0
-    let exp0 = 1.0 / (1.0 + 10.0_f64.powf((elo[1] - elo[0]) / 400.0));
-
-    // actual score for player 0
-    let score0 = winner.map_or(0.5, |p| 1.0 - p as f64);
-
-    const K: f64 = 40.0;
+fn rate(
+    game_name: &str,
+    server: u64,
+    player_id: &[u64],
+    winner: Option<usize>,
+    game: i64,
+) -> Result<()> {
+    let before = [
+        get(server, player_id[0], game_name)?,
+        get(server, player_id[1], game_name)?,
+    ];
 
-    // calculate elo addition/subtraction
-    let d_elo = K * (score0 - exp0);
+    let elo = rate_game(before, winner);
 
     // update elo
-    set(server, player_id[0], game_name, elo[0] + d_elo)?;
-    set(server, player_id[1], game_name, elo[1] - d_elo)?;
+    set(server, player_id[0], game_name, elo[0])?;
+    set(server, player_id[1], game_name, elo[1])?;
+
+    for p in 0..2 {
+        let (player, opponent) = (player_id[p], player_id[1 - p]);
+        add_change(
+            server,
+            player,
+            opponent,
+            game_name,
+            game,
+            [before[p], elo[p]],
+        )?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,82 @@
+use super::*;
+use oxidized::game::elo::sparkline;
+
+#[group]
+#[help_available]
+#[only_in(guilds)]
+#[commands(history)]
+pub struct RatingHistory;
+
+/// How many of the latest games are listed
+const LISTED_GAMES: usize = 10;
+
+/// How many of the latest ratings the sparkline shows, so it fits into the embed
+const SPARKLINE_RATINGS: usize = 60;
+
+#[command]
+#[min_args(1)]
+#[description = "Shows how the rating of a player changed over their rated games."]
+#[usage = "<game> [player]"]
+#[example = "connect4 @Oxidized"]
+async fn history(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = msg.guild_id.ok_or("not sent in a guild")?.get();
+
+    let game = msg.args().single::<String>()?;
+    let game = game_names()
+        .into_iter()
+        .find(|&name| name == game)
+        .ok_or("unknown game")?;
+    let player = msg.mentions.first().unwrap_or(&msg.author);
+
+    create_tables(game)?;
+    let changes = elo::history(server, player.id.get(), game)?;
+
+    let last = match changes.last() {
+        Some(last) => last,
+        None => {
+            return msg
+                .err_reply(
+                    ctx,
+                    &format!("{} didn't play a rated game yet.", player.mention()),
+                )
+                .await
+        }
+    };
+
+    let mut ratings = vec![changes[0].before];
+    ratings.extend(changes.iter().map(|change| change.after));
+    let best = ratings.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let lines = changes
+        .iter()
+        .rev()
+        .take(LISTED_GAMES)
+        .map(|change| {
+            format!(
+                "`#{:<4} {} {:0>4} {:>+4}` vs {}\n",
+                change.game,
+                change.time.format("%Y-%m-%d"),
+                change.after as i64,
+                change.after as i64 - change.before as i64,
+                change.opponent.mention()
+            )
+        })
+        .collect::<Vec<_>>();
+    let lines = split_into_fields(&lines, "");
+
+    let description = format!(
+        "```\n{}\n```Rating: `{}`, best: `{}`, games: `{}`\n\n{}",
+        sparkline(&ratings[ratings.len().saturating_sub(SPARKLINE_RATINGS)..]),
+        last.after as i64,
+        best as i64,
+        changes.len(),
+        lines[0]
+    );
+
+    msg.ereply(ctx, |e| {
+        e.title(format!("{} Rating History of {}", game, player.name))
+            .description(description)
+    })
+    .await?;
+    Ok(())
+}
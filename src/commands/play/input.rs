@@ -0,0 +1,382 @@
+use super::*;
+use oxidized::game::util::NUMBERS;
+
+/// How players make their moves
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Controls {
+    /// buttons and select menus below the board
+    Components,
+    /// reactions on the board, which servers can choose instead
+    Reactions,
+}
+
+impl Controls {
+    /// the controls a server chose
+    pub fn of_server(server: u64) -> Result<Self> {
+        Ok(if config::reaction_input(server)? {
+            Self::Reactions
+        } else {
+            Self::Components
+        })
+    }
+}
+
+/// Discord specific parts of a game
+pub trait DiscordGame<T>: PvpGame<T> {
+    /// Input Method
+    fn input(controls: Controls) -> Box<dyn InputMethod<T> + Send + Sync>;
+}
+
+#[async_trait]
+pub trait InputMethod<Input: 'static> {
+    async fn prepare(&self, _: &Context, _: &Message) -> CommandResult {
+        Ok(())
+    }
+    async fn receive_input(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        player: &UserId,
+        timeout: Duration,
+    ) -> CommandResult<Input>;
+}
+
+/// One button per move, the index of the pressed button is the move
+pub struct ButtonInput {
+    labels: Vec<String>,
+    /// buttons per row, discord allows at most five
+    width: usize,
+}
+
+impl ButtonInput {
+    pub fn new(labels: impl Iterator<Item = String>, width: usize) -> Self {
+        Self {
+            labels: labels.collect(),
+            width,
+        }
+    }
+}
+
+#[async_trait]
+impl InputMethod<usize> for ButtonInput {
+    async fn prepare(&self, ctx: &Context, msg: &Message) -> CommandResult {
+        let rows = self
+            .labels
+            .chunks(self.width)
+            .enumerate()
+            .map(|(row, labels)| {
+                let buttons = labels
+                    .iter()
+                    .enumerate()
+                    .map(|(col, label)| {
+                        CreateButton::new(format!("{}", row * self.width + col))
+                            .label(label)
+                            .style(ButtonStyle::Secondary)
+                    })
+                    .collect();
+                CreateActionRow::Buttons(buttons)
+            })
+            .collect();
+
+        msg.channel_id
+            .edit_message(ctx, msg.id, EditMessage::new().components(rows))
+            .await?;
+        Ok(())
+    }
+    async fn receive_input(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        player: &UserId,
+        timeout: Duration,
+    ) -> CommandResult<usize> {
+        let interaction = msg
+            .await_component_interaction(ctx)
+            .author_id(*player)
+            .timeout(timeout)
+            .await
+            .ok_or("no button pressed")?;
+
+        interaction
+            .create_response(ctx, CreateInteractionResponse::Acknowledge)
+            .await?;
+
+        Ok(interaction.data.custom_id.parse()?)
+    }
+}
+
+pub struct ReactionInput(pub Vec<ReactionType>);
+#[async_trait]
+impl InputMethod<usize> for ReactionInput {
+    async fn prepare(&self, ctx: &Context, msg: &Message) -> CommandResult {
+        for r in self.0.iter() {
+            msg.react(ctx, r.clone()).await?;
+        }
+        Ok(())
+    }
+    async fn receive_input(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        player: &UserId,
+        timeout: Duration,
+    ) -> CommandResult<usize> {
+        let player = *player;
+        // other reactions like resigning are handled by the game
+        let moves = self.0.clone();
+        let reaction = msg
+            .await_reaction(ctx)
+            .filter(move |r| r.user_id == Some(player) && moves.contains(&r.emoji))
+            .timeout(timeout)
+            .await
+            .ok_or("no reaction")?;
+
+        let idx = self
+            .0
+            .iter()
+            .position(|e| *e == reaction.emoji)
+            .ok_or("no fitting reaction")?;
+
+        // so the same move can be chosen again later
+        reaction.delete(ctx).await.ok();
+        Ok(idx)
+    }
+}
+
+/// Turns the text of a move into the move
+pub type Parser<T> = Box<dyn Send + Sync + Fn(&str) -> CommandResult<T>>;
+
+/// A select menu with a placeholder and the labels and values of its options
+pub struct Menu(pub &'static str, pub Vec<(String, String)>);
+
+/// Select menus that make up a move together, which is then confirmed with a button.
+///
+/// The selected values are concatenated and parsed like a typed move.
+pub struct SelectInput<T> {
+    menus: Vec<Menu>,
+    parse: Parser<T>,
+    selected: Mutex<Vec<Option<String>>>,
+}
+
+impl<T> SelectInput<T> {
+    pub fn new(menus: Vec<Menu>, parse: Parser<T>) -> Self {
+        let selected = Mutex::new(vec![None; menus.len()]);
+        Self {
+            menus,
+            parse,
+            selected,
+        }
+    }
+
+    /// the menus with the current selection, as discord forgets it when the message is edited
+    fn components(&self, selected: &[Option<String>]) -> Vec<CreateActionRow> {
+        let mut rows = self
+            .menus
+            .iter()
+            .zip(selected.iter())
+            .enumerate()
+            .map(|(idx, (Menu(placeholder, options), selected))| {
+                let options = options
+                    .iter()
+                    .map(|(label, value)| {
+                        CreateSelectMenuOption::new(label, value)
+                            .default_selection(Some(value) == selected.as_ref())
+                    })
+                    .collect();
+                let kind = CreateSelectMenuKind::String { options };
+                CreateActionRow::SelectMenu(
+                    CreateSelectMenu::new(format!("{}", idx), kind).placeholder(*placeholder),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        rows.push(CreateActionRow::Buttons(vec![CreateButton::new("play")
+            .label("Play")
+            .style(ButtonStyle::Primary)]));
+        rows
+    }
+}
+
+#[async_trait]
+impl<T: 'static + Send + Sync> InputMethod<T> for SelectInput<T> {
+    async fn prepare(&self, ctx: &Context, msg: &Message) -> CommandResult {
+        let selected = self.selected.lock().await;
+        let components = self.components(&selected);
+        msg.channel_id
+            .edit_message(ctx, msg.id, EditMessage::new().components(components))
+            .await?;
+        Ok(())
+    }
+    async fn receive_input(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        player: &UserId,
+        timeout: Duration,
+    ) -> CommandResult<T> {
+        let end = Instant::now() + timeout;
+
+        loop {
+            let interaction = msg
+                .await_component_interaction(ctx)
+                .author_id(*player)
+                .timeout(end.saturating_duration_since(Instant::now()))
+                .await
+                .ok_or("no input")?;
+
+            let mut selected = self.selected.lock().await;
+
+            if let ComponentInteractionDataKind::StringSelect { values } = &interaction.data.kind {
+                let idx = interaction.data.custom_id.parse::<usize>()?;
+                *selected.get_mut(idx).ok_or("unknown menu")? = values.first().cloned();
+
+                let update =
+                    CreateInteractionResponseMessage::new().components(self.components(&selected));
+                interaction
+                    .create_response(ctx, CreateInteractionResponse::UpdateMessage(update))
+                    .await?;
+                continue;
+            }
+
+            // the play button was pressed
+            if selected.iter().any(Option::is_none) {
+                interaction
+                    .create_response(ctx, CreateInteractionResponse::Acknowledge)
+                    .await?;
+                return Err("incomplete move".into());
+            }
+
+            let text = selected.iter().flatten().cloned().collect::<String>();
+            selected.iter_mut().for_each(|s| *s = None);
+
+            let update =
+                CreateInteractionResponseMessage::new().components(self.components(&selected));
+            interaction
+                .create_response(ctx, CreateInteractionResponse::UpdateMessage(update))
+                .await?;
+
+            return (self.parse)(&text);
+        }
+    }
+}
+
+pub struct TextInput<T>(pub Parser<T>);
+#[async_trait]
+impl<T: 'static + Send + Sync> InputMethod<T> for TextInput<T> {
+    async fn receive_input(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        player: &UserId,
+        timeout: Duration,
+    ) -> CommandResult<T> {
+        let msg = msg
+            .channel_id
+            .await_reply(ctx)
+            .author_id(*player)
+            .timeout(timeout)
+            .await
+            .ok_or("no message")?;
+
+        let parsed = (self.0)(&msg.content);
+
+        if parsed.is_ok() {
+            msg.delete(ctx).await.ok();
+        }
+
+        parsed
+    }
+}
+
+/// Takes the input of whichever method is used first
+pub struct EitherInput<A, B>(pub A, pub B);
+#[async_trait]
+impl<T, A, B> InputMethod<T> for EitherInput<A, B>
+where
+    T: 'static + Send + Sync,
+    A: InputMethod<T> + Send + Sync,
+    B: InputMethod<T> + Send + Sync,
+{
+    async fn prepare(&self, ctx: &Context, msg: &Message) -> CommandResult {
+        self.0.prepare(ctx, msg).await?;
+        self.1.prepare(ctx, msg).await
+    }
+    async fn receive_input(
+        &self,
+        ctx: &Context,
+        msg: &Message,
+        player: &UserId,
+        timeout: Duration,
+    ) -> CommandResult<T> {
+        tokio::select! {
+            input = self.0.receive_input(ctx, msg, player, timeout) => input,
+            input = self.1.receive_input(ctx, msg, player, timeout) => input,
+        }
+    }
+}
+
+pub fn number_emoji(num: usize) -> ReactionType {
+    ReactionType::Unicode(NUMBERS[num].clone())
+}
+
+/// One button or reaction per number, the index of the number is the move
+fn number_input(
+    numbers: std::ops::Range<usize>,
+    width: usize,
+    controls: Controls,
+) -> Box<dyn InputMethod<usize> + Send + Sync> {
+    match controls {
+        Controls::Components => {
+            Box::new(ButtonInput::new(numbers.map(|n| format!("{}", n)), width))
+        }
+        Controls::Reactions => Box::new(ReactionInput(numbers.map(number_emoji).collect())),
+    }
+}
+
+impl DiscordGame<usize> for tictactoe::TTTField {
+    fn input(controls: Controls) -> Box<dyn InputMethod<usize> + Send + Sync> {
+        number_input(1..10, 3, controls)
+    }
+}
+
+impl DiscordGame<usize> for ultimate::UltimateGame {
+    fn input(controls: Controls) -> Box<dyn InputMethod<usize> + Send + Sync> {
+        number_input(1..10, 3, controls)
+    }
+}
+
+impl DiscordGame<usize> for connect4::Connect4 {
+    fn input(controls: Controls) -> Box<dyn InputMethod<usize> + Send + Sync> {
+        number_input(0..connect4::COLS, 4, controls)
+    }
+}
+
+impl DiscordGame<pentago::PMove> for pentago::Pentago {
+    fn input(controls: Controls) -> Box<dyn InputMethod<pentago::PMove> + Send + Sync> {
+        let text = TextInput(Box::new(|text| Ok(text.parse()?)));
+        if controls == Controls::Reactions {
+            // there are too many moves for reactions, so they are typed
+            return Box::new(text);
+        }
+
+        let numbers = |name: &'static str, count: usize| {
+            let options = (1..=count)
+                .map(|n| (format!("{} {}", name, n), format!("{}", n)))
+                .collect();
+            Menu(name, options)
+        };
+        let rotation = vec![
+            (String::from("Anticlockwise"), String::from("A")),
+            (String::from("Clockwise"), String::from("C")),
+        ];
+        let menus = vec![
+            numbers("Column", 6),
+            numbers("Row", 6),
+            numbers("Subfield", 4),
+            Menu("Rotation", rotation),
+        ];
+
+        let select = SelectInput::new(menus, Box::new(|text| Ok(text.parse()?)));
+        Box::new(EitherInput(select, text))
+    }
+}
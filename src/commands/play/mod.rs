@@ -1,22 +1,33 @@
+use super::config;
+use super::filter::*;
 use crate::ser::*;
 use crate::{prelude::*, tryc};
+use oxidized::game::analysis::{Opening, Review};
+use oxidized::game::clock::*;
+use oxidized::game::render::Style;
+use oxidized::game::*;
 use rusqlite::{params, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::*;
 
-mod connect4;
+mod analysis;
 mod elo;
-mod mcts;
-mod minimax;
-mod pentago;
-mod random_ai;
+mod history;
+mod input;
+mod persist;
+mod queue;
 mod runner;
-mod tictactoe;
-mod ultimate;
-mod util;
-use minimax::*;
-use random_ai::*;
+mod style;
+mod tournament;
+use input::*;
+use persist::SavedGame;
 use runner::GameRunner;
-use util::*;
+
+pub use analysis::ANALYSIS_GROUP;
+pub use history::RATINGHISTORY_GROUP;
+pub use queue::QUEUE_GROUP;
+pub use style::BOARDSTYLE_GROUP;
+pub use tournament::TOURNAMENTS_GROUP;
 
 macro_rules! make_games {
     ($($(#[$meta:meta])* game $name:ident ($struct:expr, $timeout:expr); )*) => {
@@ -26,18 +37,19 @@ macro_rules! make_games {
                 #[command]
                 #[only_in(guilds)]
                 #[bucket("game")]
-                #[usage = "[casual] <enemy_player>"]
+                #[usage = "[casual] <enemy_player> [60 | 5+3 | 24h]"]
                 $(
                     #[$meta]
                 )*
                 async fn $name(ctx: &Context, prompt: &Message) -> CommandResult {
-                    let mut runner = GameRunner::new(ctx, prompt, $struct, stringify!($name), $timeout as f64).await?;
+                    let mut runner = GameRunner::new(ctx, prompt, $struct, stringify!($name), TimeControl::PerMove(Duration::from_secs_f64($timeout))).await?;
                     runner.run(ctx).await
                 }
             )*
 
             #[group]
             #[help_available]
+            #[checks(GameChannel)]
             #[prefix = "play"]
             #[commands($(
                 $name
@@ -52,6 +64,7 @@ macro_rules! make_games {
             $(
                 #[command]
                 #[only_in(guilds)]
+                #[usage = "[global]"]
                 async fn $name(ctx: &Context, msg: &Message) -> CommandResult {
                     fn title<T, G: PvpGame<T>>(_: &G) -> &'static str { G::title() }
                     leaderboard(ctx, msg, stringify!($name), title(&$struct)).await
@@ -68,6 +81,76 @@ macro_rules! make_games {
             pub struct Leaderboard;
         }
 
+        /// continues a game of any kind that was running when the bot stopped
+        async fn resume(ctx: &Context, saved: SavedGame) -> CommandResult {
+            let board = saved.board;
+            match saved.game.as_str() {
+                $(
+                    stringify!($name) => {
+                        let runner = GameRunner::resume(ctx, $struct, stringify!($name), saved).await;
+                        let mut runner = match runner {
+                            Ok(runner) => runner,
+                            Err(why) => {
+                                // the board message is gone or the game is broken
+                                persist::delete(board)?;
+                                return Err(why);
+                            }
+                        };
+                        runner.run(ctx).await
+                    }
+                )*
+                _ => Err("unknown game")?,
+            }
+        }
+
+        /// plays a game of a tournament, returns the winner
+        async fn play_pairing(
+            ctx: &Context,
+            prompt: &Message,
+            game: &str,
+            players: [UserId; 2],
+            time_control: TimeControl,
+        ) -> CommandResult<Option<usize>> {
+            match game {
+                $(
+                    stringify!($name) => {
+                        let mut runner = GameRunner::pairing(ctx, prompt, $struct, stringify!($name), players, time_control).await?;
+                        runner.run(ctx).await?;
+                        Ok(runner.winner())
+                    }
+                )*
+                _ => Err("unknown game")?,
+            }
+        }
+
+        /// reviews every move of a logged game
+        fn review_game(game: &str, moves: &[u8]) -> CommandResult<Vec<(String, Review)>> {
+            match game {
+                $(
+                    stringify!($name) => analysis::review($struct, moves),
+                )*
+                _ => Err("unknown game")?,
+            }
+        }
+
+        /// the most common first moves of logged games
+        fn game_openings(
+            game: &str,
+            games: &[(Vec<u8>, Option<usize>)],
+        ) -> CommandResult<Vec<(String, Opening)>> {
+            match game {
+                $(
+                    stringify!($name) => Ok(analysis::describe_openings($struct, games)),
+                )*
+                _ => Err("unknown game")?,
+            }
+        }
+
+        /// names of all games
+        fn game_names() -> Vec<&'static str> {
+            vec![$(stringify!($name)),*]
+        }
+
         pub use game::GAMES_GROUP;
         pub use score::LEADERBOARD_GROUP;
     };
@@ -90,138 +173,43 @@ You win if you have three in a row, column or diagonal in the big field."
 A person wins if four discs of the same color are arranged in a row, column, or diagonal.")]
     game connect4(connect4::Connect4::default(), 60.0);
 
-    #[description("The game is played on a 6×6 board divided into four 3×3 sub-boards (or quadrants). Taking turns, the two players place a marble of their color onto an unoccupied space on the board, and then rotate one of the sub-boards by 90 degrees either clockwise or anti-clockwise. A player wins by getting five of their marbles in a vertical, horizontal or diagonal row (either before or after the sub-board rotation in their move). **Important**: the game is played by text. Type `XYSR` to make a move. `X` and `Y` are the location of your next move. `S` is the number of the subfield you want to rotate. `R` is the direction of the rotation of the subfield (**A**nticlockwise or **C**lockwise). Example: `314A` (place marble on (3, 1), rotate field 4 90 degress anticlockwise).")]
+    #[description("The game is played on a 6×6 board divided into four 3×3 sub-boards (or quadrants). Taking turns, the two players place a marble of their color onto an unoccupied space on the board, and then rotate one of the sub-boards by 90 degrees either clockwise or anti-clockwise. A player wins by getting five of their marbles in a vertical, horizontal or diagonal row (either before or after the sub-board rotation in their move). Choose your move with the menus below the board, or type `XYSR`. `X` and `Y` are the location of your next move. `S` is the number of the subfield you want to rotate. `R` is the direction of the rotation of the subfield (**A**nticlockwise or **C**lockwise). Example: `314A` (place marble on (3, 1), rotate field 4 90 degress anticlockwise).")]
     game pentago(pentago::Pentago::default(), 60.0);
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum GameState {
-    Running,
-    Tie,
-    Invalid,
-    Win(usize),
-}
+static RESUMED: AtomicBool = AtomicBool::new(false);
 
-impl GameState {
-    fn is_finished(&self) -> bool {
-        match self {
-            Self::Tie => true,
-            Self::Win(_) => true,
-            _ => false,
-        }
+/// Continues the games that were running when the bot stopped, only once after it starts
+pub fn resume_games(ctx: &Context) -> CommandResult {
+    if RESUMED.swap(true, Ordering::SeqCst) {
+        return Ok(());
     }
-}
 
-/// All functions a game must possess
-pub trait PvpGame<T> {
-    /// Title of the game
-    fn title() -> &'static str;
-    /// Input Method
-    fn input() -> Box<dyn InputMethod<T> + Send + Sync>;
-    /// Display the current board in the discord message
-    fn draw(&self) -> String;
-    /// Make a game move
-    fn make_move(&mut self, action: T, person: usize) -> GameState;
-    fn status(&self) -> GameState;
-    fn winner(&self) -> Option<usize> {
-        if let GameState::Win(winner) = self.status() {
-            Some(winner)
-        } else {
-            None
-        }
-    }
-    fn ai() -> Option<Box<dyn AiPlayer<T, Self> + Send + Sync>> {
-        None
-    }
-    fn possible_moves(&self, _player: usize) -> Vec<T> {
-        Vec::new()
-    }
-    fn figures() -> Vec<String>;
-    fn is_empty(&self) -> bool;
-}
-
-pub trait AiPlayer<T, G: PvpGame<T>> {
-    fn make_move(&mut self, game: &G, player_id: usize) -> T;
-}
-
-#[async_trait]
-pub trait InputMethod<Input: 'static> {
-    async fn prepare(&self, _: &Context, _: &Message) -> CommandResult {
-        Ok(())
-    }
-    async fn receive_input(
-        &self,
-        ctx: &Context,
-        msg: &Message,
-        player: &UserId,
-        timeout: Duration,
-    ) -> CommandResult<Input>;
-}
-
-struct ReactionInput(pub Vec<ReactionType>);
-#[async_trait]
-impl InputMethod<usize> for ReactionInput {
-    async fn prepare(&self, ctx: &Context, msg: &Message) -> CommandResult {
-        for r in self.0.iter() {
-            msg.react(ctx, r.clone()).await?;
-        }
-        Ok(())
-    }
-    async fn receive_input(
-        &self,
-        ctx: &Context,
-        msg: &Message,
-        player: &UserId,
-        timeout: Duration,
-    ) -> CommandResult<usize> {
-        let reaction = msg
-            .await_reaction(ctx)
-            .author_id(*player.as_u64())
-            .removed(true)
-            .timeout(timeout)
-            .await
-            .ok_or("no reaction")?;
-
-        let idx = self
-            .0
-            .iter()
-            .position(|e| *e == reaction.as_inner_ref().emoji)
-            .ok_or("no fitting reaction")?;
-
-        Ok(idx)
+    for saved in persist::load_all()? {
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            let board = saved.board;
+            if let Err(why) = resume(&ctx, saved).await {
+                tracing::info!("Resumed game {} failed: {}", board, why);
+            }
+        });
     }
+    Ok(())
 }
 
-struct TextInput<T>(pub Box<dyn Send + Sync + Fn(&str) -> CommandResult<T>>);
-#[async_trait]
-impl<T: 'static + Send + Sync> InputMethod<T> for TextInput<T> {
-    async fn receive_input(
-        &self,
-        ctx: &Context,
-        msg: &Message,
-        player: &UserId,
-        timeout: Duration,
-    ) -> CommandResult<T> {
-        let msg = msg
-            .channel_id
-            .await_reply(ctx)
-            .author_id(*player.as_u64())
-            .timeout(timeout)
-            .await
-            .ok_or("no message")?;
-
-        let parsed = (self.0)(&msg.content);
-
-        if parsed.is_ok() {
-            msg.delete(ctx).await.ok();
-        }
-
-        parsed
-    }
-}
+/// How many players the global leaderboards show
+const GLOBAL_PLAYERS: usize = 25;
 
 async fn leaderboard(ctx: &Context, msg: &Message, game: &str, game_name: &str) -> CommandResult {
-    let server = format!("{}", msg.guild_id.ok_or("not sent in a guild")?);
+    let guild = msg.guild_id.ok_or("not sent in a guild")?.get();
+    let global = msg.args().single::<String>().ok().as_deref() == Some("global");
+
+    create_tables(game)?;
+    let inactive = match config::inactive_days(guild)? {
+        Some(days) if !global => elo::inactive_players(guild, game, days)?,
+        _ => Vec::new(),
+    };
+    let server = format!("{}", if global { elo::GLOBAL } else { guild });
 
     let players = {
         let db = db()?;
@@ -236,7 +224,10 @@ async fn leaderboard(ctx: &Context, msg: &Message, game: &str, game_name: &str)
         let mut players = Vec::new();
         for entry in players_iter {
             let (player, elo) = tryc!(entry.ok());
-            let player = UserId(player.parse::<u64>().unwrap());
+            let player = UserId::new(player.parse::<u64>().unwrap());
+            if inactive.contains(&player) {
+                continue;
+            }
             players.push((player, elo));
         }
 
@@ -246,11 +237,18 @@ async fn leaderboard(ctx: &Context, msg: &Message, game: &str, game_name: &str)
 
     let mut leaderboard = Vec::new();
 
-    for (idx, (user, elo)) in players.into_iter().enumerate() {
+    let shown = if global {
+        GLOBAL_PLAYERS
+    } else {
+        players.len()
+    };
+    for (idx, (user, elo)) in players.into_iter().take(shown).enumerate() {
         let rank = rank_string(idx + 1);
         let points = format!("{:0>4}", elo as i64);
 
+        // players of other servers can't be mentioned
         let user = match user.to_user(ctx).await {
+            Ok(user) if global => user.name,
             Ok(user) => user.mention().to_string(),
             Err(_) => String::from("<invalid user>"),
         };
@@ -261,8 +259,12 @@ async fn leaderboard(ctx: &Context, msg: &Message, game: &str, game_name: &str)
     let leaderboard = split_into_fields(&leaderboard, "This leaderboard is empty");
 
     msg.ereply(ctx, |e| {
-        e.title(format!("{} Leaderboard", game_name));
-        e.description(&leaderboard[0])
+        let title = if global {
+            format!("Global {} Leaderboard", game_name)
+        } else {
+            format!("{} Leaderboard", game_name)
+        };
+        e.title(title).description(&leaderboard[0])
     })
     .await?;
 
@@ -280,25 +282,27 @@ fn rank_string(rank: usize) -> String {
     format!("{: >3}{}", rank, suffix)
 }
 
+/// Stores a finished game, returns its row in the game logs
 fn log_game(
     game: &str,
     server: u64,
     player_id: &[u64],
     moves: &[u8],
     winner: Option<usize>,
-) -> Result<()> {
+) -> Result<i64> {
     let player1 = format!("{}", player_id[0]);
     let player2 = format!("{}", player_id[1]);
     let server = format!("{}", server);
     let result = winner.map_or(0, |win| win as u8 + 1);
-    db()?.execute(
+    let db = db()?;
+    db.execute(
         &format!(
             "INSERT INTO {} (server, player1, player2, moves, result) VALUES (?1, ?2, ?3, ?4, ?5);",
             games_table(game)
         ),
         params!(server, player1, player2, &moves, result),
     )?;
-    Ok(())
+    Ok(db.last_insert_rowid())
 }
 
 fn games_table(game: &str) -> String {
@@ -309,6 +313,10 @@ fn elo_table(game: &str) -> String {
     format!("{}_elo", game)
 }
 
+fn history_table(game: &str) -> String {
+    format!("{}_history", game)
+}
+
 fn create_tables(game: &str) -> Result<()> {
     db()?.execute(
         &format!("CREATE TABLE IF NOT EXISTS {} (server TEXT, player1 TEXT, player2 TEXT, moves BLOB, result INTEGER);", games_table(game)),
@@ -321,5 +329,12 @@ fn create_tables(game: &str) -> Result<()> {
         ),
         params!(),
     )?;
+    db()?.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} (server TEXT, player TEXT, opponent TEXT, game INTEGER, before REAL, after REAL, time INTEGER);",
+            history_table(game)
+        ),
+        params!(),
+    )?;
     Ok(())
 }
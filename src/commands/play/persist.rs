@@ -0,0 +1,117 @@
+//! Running games are stored after every move, so they can be continued after a restart.
+
+use super::*;
+use chrono::prelude::*;
+
+/// Everything that is needed to continue a game
+pub struct SavedGame {
+    pub game: String,
+    pub server: u64,
+    pub channel: ChannelId,
+    pub board: MessageId,
+    pub players: [UserId; 2],
+    /// whether the second player is the AI
+    pub ai: bool,
+    pub competitive: bool,
+    pub moves: Vec<u8>,
+    pub time_control: TimeControl,
+    /// remaining time of both players at the start of their move
+    pub remaining: [Duration; 2],
+    /// when the current move began
+    pub turn_start: DateTime<Utc>,
+}
+
+fn create_table() -> Result<()> {
+    db()?.execute(
+        "CREATE TABLE IF NOT EXISTS running_games (board TEXT, channel TEXT, server TEXT, game TEXT, player1 TEXT, player2 TEXT, ai INTEGER, competitive INTEGER, moves BLOB, time_control TEXT, remaining1 INTEGER, remaining2 INTEGER, turn_start INTEGER);",
+        params!(),
+    )?;
+    Ok(())
+}
+
+pub fn save(game: &SavedGame) -> Result<()> {
+    create_table()?;
+    delete(game.board)?;
+    db()?.execute(
+        "INSERT INTO running_games (board, channel, server, game, player1, player2, ai, competitive, moves, time_control, remaining1, remaining2, turn_start) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13);",
+        params!(
+            format!("{}", game.board),
+            format!("{}", game.channel),
+            format!("{}", game.server),
+            game.game,
+            format!("{}", game.players[0]),
+            format!("{}", game.players[1]),
+            game.ai,
+            game.competitive,
+            &game.moves,
+            game.time_control.to_string(),
+            game.remaining[0].as_millis() as i64,
+            game.remaining[1].as_millis() as i64,
+            game.turn_start.timestamp_millis(),
+        ),
+    )?;
+    Ok(())
+}
+
+/// Removes a game that ended
+pub fn delete(board: MessageId) -> Result<()> {
+    create_table()?;
+    db()?.execute(
+        "DELETE FROM running_games WHERE board = ?1;",
+        params!(format!("{}", board)),
+    )?;
+    Ok(())
+}
+
+pub fn load_all() -> CommandResult<Vec<SavedGame>> {
+    create_table()?;
+    let db = db()?;
+    let mut stmt = db.prepare("SELECT board, channel, server, game, player1, player2, ai, competitive, moves, time_control, remaining1, remaining2, turn_start FROM running_games")?;
+
+    let rows = stmt.query_map(params!(), |row| {
+        Ok((
+            [
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ],
+            row.get::<_, String>(3)?,
+            row.get::<_, bool>(6)?,
+            row.get::<_, bool>(7)?,
+            row.get::<_, Vec<u8>>(8)?,
+            row.get::<_, String>(9)?,
+            [row.get::<_, i64>(10)?, row.get::<_, i64>(11)?],
+            row.get::<_, i64>(12)?,
+        ))
+    })?;
+
+    let mut games = Vec::new();
+    for row in rows {
+        let (ids, game, ai, competitive, moves, time_control, remaining, turn_start) = row?;
+        let ids = ids
+            .iter()
+            .map(|id| id.parse::<u64>())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let millis = |ms: i64| Duration::from_millis(ms.max(0) as u64);
+
+        games.push(SavedGame {
+            game,
+            server: ids[2],
+            channel: ChannelId::new(ids[1]),
+            board: MessageId::new(ids[0]),
+            players: [UserId::new(ids[3]), UserId::new(ids[4])],
+            ai,
+            competitive,
+            moves,
+            time_control: time_control.parse()?,
+            remaining: [millis(remaining[0]), millis(remaining[1])],
+            turn_start: Utc
+                .timestamp_millis_opt(turn_start)
+                .single()
+                .ok_or("invalid time")?,
+        });
+    }
+    Ok(games)
+}
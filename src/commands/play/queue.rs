@@ -0,0 +1,222 @@
+use super::*;
+use oxidized::game::matchmaking::*;
+
+#[group]
+#[help_available]
+#[only_in(guilds)]
+#[checks(GameChannel)]
+#[prefix = "queue"]
+#[default_command(queue)]
+#[commands(queue, leave)]
+pub struct Queue;
+
+/// How often the queue looks for matches
+const INTERVAL: Duration = Duration::from_secs(5);
+
+struct Waiting {
+    server: u64,
+    game: &'static str,
+    player: UserId,
+    rating: f64,
+    since: Instant,
+    /// the message the player joined the queue with
+    prompt: Message,
+}
+
+lazy_static! {
+    static ref WAITING: Mutex<Vec<Waiting>> = Mutex::new(Vec::new());
+}
+
+static MATCHMAKING: AtomicBool = AtomicBool::new(false);
+
+#[command]
+#[description = "Waits for an opponent with a similar rating for a competitive game. The longer you wait, the bigger the rating difference to your opponent can be. Without a game, this shows who is waiting."]
+#[usage = "[game]"]
+#[example = "connect4"]
+async fn queue(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = msg.guild_id.ok_or("not sent in a guild")?.get();
+
+    let game = match msg.args().single::<String>() {
+        Ok(game) => game,
+        Err(_) => return show_queue(ctx, msg, server).await,
+    };
+    let game = game_names()
+        .into_iter()
+        .find(|&name| name == game)
+        .ok_or("unknown game")?;
+
+    if !config::rated(server)? {
+        return msg
+            .err_reply(ctx, "Games aren't rated on this server.")
+            .await;
+    }
+
+    create_tables(game)?;
+    let rating = elo::get(server, msg.author.id.get(), game)?;
+
+    {
+        let mut waiting = WAITING.lock().await;
+        if waiting
+            .iter()
+            .any(|w| w.server == server && w.player == msg.author.id)
+        {
+            return msg
+                .err_reply(ctx, "You are already waiting for a game.")
+                .await;
+        }
+        waiting.push(Waiting {
+            server,
+            game,
+            player: msg.author.id,
+            rating,
+            since: Instant::now(),
+            prompt: msg.clone(),
+        });
+    }
+
+    if !MATCHMAKING.swap(true, Ordering::SeqCst) {
+        let ctx = ctx.clone();
+        tokio::spawn(async move { matchmaking(ctx).await });
+    }
+
+    msg.ereply(ctx, |e| {
+        e.title("Queue").description(format!(
+            "{} is waiting for a game of {} with a rating of {}.",
+            msg.author.mention(),
+            game,
+            rating as i64
+        ))
+    })
+    .await?;
+    Ok(())
+}
+
+#[command]
+#[description = "Stops waiting for a game."]
+async fn leave(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = msg.guild_id.ok_or("not sent in a guild")?.get();
+
+    let mut waiting = WAITING.lock().await;
+    let before = waiting.len();
+    waiting.retain(|w| !(w.server == server && w.player == msg.author.id));
+    if waiting.len() == before {
+        return msg.err_reply(ctx, "You aren't waiting for a game.").await;
+    }
+
+    msg.ereply(ctx, |e| {
+        e.title("Queue")
+            .description(format!("{} left the queue.", msg.author.mention()))
+    })
+    .await?;
+    Ok(())
+}
+
+async fn show_queue(ctx: &Context, msg: &Message, server: u64) -> CommandResult {
+    let lines = WAITING
+        .lock()
+        .await
+        .iter()
+        .filter(|w| w.server == server)
+        .map(|w| {
+            let waited = w.since.elapsed();
+            format!(
+                "`{:<10} {:0>4} ±{:<4} {:>6}` {}\n",
+                w.game,
+                w.rating as i64,
+                window(waited) as i64,
+                format_duration(waited),
+                w.player.mention()
+            )
+        })
+        .collect::<Vec<_>>();
+    let fields = split_into_fields(&lines, "Nobody is waiting for a game.");
+
+    msg.ereply(ctx, |e| e.title("Queue").description(&fields[0]))
+        .await?;
+    Ok(())
+}
+
+/// Pairs waiting players of the same server and game, forever
+async fn matchmaking(ctx: Context) {
+    loop {
+        tokio::time::sleep(INTERVAL).await;
+
+        let matches = {
+            let mut waiting = WAITING.lock().await;
+            let mut matches = Vec::new();
+            while let Some((a, b)) = next_match(&waiting) {
+                // the one waiting longer moves first, and is removed last to keep the index valid
+                let second = waiting.remove(b);
+                let first = waiting.remove(a);
+                matches.push((first, second));
+            }
+            matches
+        };
+
+        for (first, second) in matches {
+            let ctx = ctx.clone();
+            tokio::spawn(async move {
+                if let Err(why) = start_match(&ctx, first, second).await {
+                    tracing::info!("Queued game failed: {}", why);
+                }
+            });
+        }
+    }
+}
+
+/// Indices of the best pair of players waiting for the same game on the same server
+fn next_match(waiting: &[Waiting]) -> Option<(usize, usize)> {
+    for (idx, w) in waiting.iter().enumerate() {
+        let candidates = waiting
+            .iter()
+            .enumerate()
+            .filter(|(_, other)| other.server == w.server && other.game == w.game)
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        // every group is only checked once, when its first player comes up
+        if candidates[0] != idx {
+            continue;
+        }
+        let seekers = candidates
+            .iter()
+            .map(|&idx| Seeker {
+                rating: waiting[idx].rating,
+                waited: waiting[idx].since.elapsed(),
+            })
+            .collect::<Vec<_>>();
+        if let Some((a, b)) = find_match(&seekers) {
+            return Some((candidates[a], candidates[b]));
+        }
+    }
+    None
+}
+
+/// Starts the game in the first game channel of the server, or where the first player joined
+async fn start_match(ctx: &Context, first: Waiting, second: Waiting) -> CommandResult {
+    let channel = config::channels(first.server, config::Key::GameChannels)?
+        .first()
+        .copied()
+        .unwrap_or(first.prompt.channel_id);
+
+    let players = [first.player, second.player];
+    let mentions = format!("{} {}", players[0].mention(), players[1].mention());
+    let embed = CreateEmbed::new().title("Match Found").description(format!(
+        "{} ({}) plays {} ({}) in a game of {}.",
+        players[0].mention(),
+        first.rating as i64,
+        players[1].mention(),
+        second.rating as i64,
+        first.game
+    ));
+
+    // the announcement pings both players and is the prompt of the game
+    let mut prompt = channel
+        .send_message(ctx, CreateMessage::new().content(mentions).embed(embed))
+        .await?;
+    prompt.guild_id = first.prompt.guild_id;
+
+    let time_control = config::game_timeout(first.server)?
+        .unwrap_or_else(|| TimeControl::PerMove(Duration::from_secs(60)));
+    play_pairing(ctx, &prompt, first.game, players, time_control).await?;
+    Ok(())
+}
@@ -2,16 +2,74 @@ use super::*;
 
 pub struct GameRunner<T: 'static, G: PvpGame<T>> {
     game: G,
+    /// the game before the first move, to replay the moves after a takeback
+    start: G,
     game_name: &'static str,
     description: &'static str,
     mode: GameMode,
     players: Vec<Player<T, G>>,
-    timeout: f64,
+    clock: Clock,
     turn: usize,
     board: Message,
+    input: Box<dyn InputMethod<T> + Send + Sync>,
     last_turn: Instant,
+    /// time of the current move that passed before the game was resumed
+    turn_offset: Duration,
     guild_id: u64,
     moves: Vec<T>,
+    style: Style,
+    /// description of the last move
+    last_move: Option<String>,
+    /// number of moves on the image that is attached to the board message
+    drawn_moves: Option<usize>,
+    ending: Option<Ending>,
+}
+
+/// How a game ended that wasn't decided on the board
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Ending {
+    Resignation(usize),
+    Draw,
+    /// the player ran out of time before making a move
+    Flagged(usize),
+}
+
+/// Things a player can do during a game by reacting to the board
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Action {
+    Draw,
+    Takeback,
+    Resign,
+}
+
+impl Action {
+    const ALL: [Self; 3] = [Self::Draw, Self::Takeback, Self::Resign];
+
+    fn emoji(&self) -> &'static str {
+        match self {
+            Self::Draw => "🤝",
+            Self::Takeback => "↩️",
+            Self::Resign => "🏳️",
+        }
+    }
+
+    fn of(emoji: &ReactionType) -> Option<Self> {
+        // clients don't always send the variation selector
+        let strip = |e: &str| e.trim_end_matches('\u{fe0f}').to_string();
+        match emoji {
+            ReactionType::Unicode(emoji) => Self::ALL
+                .iter()
+                .copied()
+                .find(|action| strip(action.emoji()) == strip(emoji)),
+            _ => None,
+        }
+    }
+}
+
+/// What happened while waiting for a move
+enum Event<T> {
+    Move(T),
+    Action(usize, Action),
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -38,7 +96,7 @@ impl<T, G: PvpGame<T>> Player<T, G> {
     async fn id(&self, ctx: &Context) -> UserId {
         match self {
             Self::Person(id) => *id,
-            Self::Ai(_) => ctx.cache.current_user().await.id,
+            Self::Ai(_) => ctx.cache.current_user().id,
         }
     }
 }
@@ -71,17 +129,25 @@ fn get_description(game: &'static str) -> &'static str {
     cmd.options.desc.unwrap_or("\u{200b}")
 }
 
-impl<Input: 'static + Clone, G: PvpGame<Input> + Send + Sync> GameRunner<Input, G> {
+impl<Input: 'static + Move, G: DiscordGame<Input> + Clone + Send + Sync> GameRunner<Input, G> {
     pub async fn new<'a>(
         ctx: &'a Context,
         prompt: &'a Message,
         game: G,
         game_name: &'static str,
-        timeout: f64,
+        time_control: TimeControl,
     ) -> CommandResult<Self> {
         create_tables(game_name)?;
 
-        let guild_id = *prompt.guild_id.ok_or("no server id")?.as_u64();
+        let guild_id = prompt.guild_id.ok_or("no server id")?.get();
+
+        // the time control of the prompt comes first, then the one of the server
+        let mut time_control = config::game_timeout(guild_id)?.unwrap_or(time_control);
+        for arg in prompt.args().iter::<String>() {
+            if let Ok(control) = arg?.parse() {
+                time_control = control;
+            }
+        }
 
         let challenger = prompt.author.id;
         let challenged = match prompt.mentions.iter().next() {
@@ -96,7 +162,7 @@ impl<Input: 'static + Clone, G: PvpGame<Input> + Send + Sync> GameRunner<Input,
 
         let mut mode = GameMode::Casual;
 
-        let me = ctx.cache.current_user().await.id;
+        let me = ctx.cache.current_user().id;
         let challenged = if challenged.id == me {
             // this is a bot game
 
@@ -111,18 +177,19 @@ impl<Input: 'static + Clone, G: PvpGame<Input> + Send + Sync> GameRunner<Input,
         } else {
             // against a person
 
-            // can only play competitively against other people
-            if challenger != challenged.id {
+            // can only play competitively against other people, on servers that rate games
+            if challenger != challenged.id && config::rated(guild_id)? {
                 mode = GameMode::Competitive;
             }
 
             let dialog_txt = format!(
-                "{}, you have been invited by {} to play a {} game of {}.
+                "{}, you have been invited by {} to play a {} game of {} with {}.
                 To start the game, confirm this with a reaction within ten seconds.",
                 challenged.mention(),
                 challenger.mention(),
                 mode.as_str(),
-                G::title()
+                G::title(),
+                time_control.describe()
             );
 
             if confirm_dialog(ctx, prompt, "Game Invite", &dialog_txt, &challenged).await? {
@@ -132,37 +199,328 @@ impl<Input: 'static + Clone, G: PvpGame<Input> + Send + Sync> GameRunner<Input,
             }
         };
 
+        let players = vec![Player::Person(challenger), challenged];
+        Self::create(ctx, prompt, game, game_name, mode, players, time_control).await
+    }
+
+    /// a game of a tournament, which the players don't need to accept
+    pub async fn pairing(
+        ctx: &Context,
+        prompt: &Message,
+        game: G,
+        game_name: &'static str,
+        players: [UserId; 2],
+        time_control: TimeControl,
+    ) -> CommandResult<Self> {
+        create_tables(game_name)?;
+
+        let guild_id = prompt.guild_id.ok_or("no server id")?.get();
+        let mode = if config::rated(guild_id)? {
+            GameMode::Competitive
+        } else {
+            GameMode::Casual
+        };
+
+        let players = players.iter().map(|&id| Player::Person(id)).collect();
+        Self::create(ctx, prompt, game, game_name, mode, players, time_control).await
+    }
+
+    /// sends the board of a game the players agreed on
+    async fn create(
+        ctx: &Context,
+        prompt: &Message,
+        game: G,
+        game_name: &'static str,
+        mode: GameMode,
+        players: Vec<Player<Input, G>>,
+        time_control: TimeControl,
+    ) -> CommandResult<Self> {
+        let guild_id = prompt.guild_id.ok_or("no server id")?.get();
+
         let board = prompt
             .ereply(ctx, |e| e.title(G::title()).description("Loading game..."))
             .await?;
 
-        G::input().prepare(ctx, &board).await?;
+        let input = G::input(Controls::of_server(guild_id)?);
+        input.prepare(ctx, &board).await?;
 
-        let players = vec![Player::Person(challenger), challenged];
+        let people = players
+            .iter()
+            .filter_map(|p| match p {
+                Player::Person(id) => Some(*id),
+                Player::Ai(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let style = style::board_style(guild_id, &people)?;
 
         Ok(Self {
+            start: game.clone(),
             game,
             game_name,
             description: get_description(game_name),
             mode,
             players,
-            timeout,
+            clock: Clock::new(time_control),
             turn: 0,
             board,
+            input,
             last_turn: Instant::now(),
+            turn_offset: Duration::ZERO,
             moves: Vec::new(),
             guild_id,
+            style,
+            last_move: None,
+            drawn_moves: None,
+            ending: None,
+        })
+    }
+
+    /// continues a game that was running when the bot stopped
+    pub async fn resume(
+        ctx: &Context,
+        start: G,
+        game_name: &'static str,
+        saved: SavedGame,
+    ) -> CommandResult<Self> {
+        create_tables(game_name)?;
+
+        let board = saved.channel.message(ctx, saved.board).await?;
+        let moves = decode_moves::<Input>(&saved.moves).ok_or("invalid saved moves")?;
+
+        let challenged = if saved.ai {
+            Player::Ai(G::ai().ok_or("no AI for this game")?)
+        } else {
+            Player::Person(saved.players[1])
+        };
+        let players = vec![Player::Person(saved.players[0]), challenged];
+
+        let (game, last_move) = Self::replay(ctx, &players, &start, &moves).await?;
+
+        let input = G::input(Controls::of_server(saved.server)?);
+        input.prepare(ctx, &board).await?;
+
+        let people = if saved.ai {
+            &saved.players[..1]
+        } else {
+            &saved.players[..]
+        };
+        let style = style::board_style(saved.server, people)?;
+
+        let mode = if saved.competitive {
+            GameMode::Competitive
+        } else {
+            GameMode::Casual
+        };
+
+        let turn_offset = (chrono::Utc::now() - saved.turn_start)
+            .to_std()
+            .unwrap_or_default();
+
+        Ok(Self {
+            game,
+            start,
+            game_name,
+            description: get_description(game_name),
+            mode,
+            players,
+            clock: Clock::resume(saved.time_control, saved.remaining),
+            turn: moves.len() % 2,
+            board,
+            input,
+            last_turn: Instant::now(),
+            turn_offset,
+            moves,
+            guild_id: saved.server,
+            style,
+            last_move,
+            drawn_moves: None,
+            ending: None,
         })
     }
 
-    /// when a move takes to long
+    /// makes the moves on the starting board, returns the board and the description of the last move
+    async fn replay(
+        ctx: &Context,
+        players: &[Player<Input, G>],
+        start: &G,
+        moves: &[Input],
+    ) -> CommandResult<(G, Option<String>)> {
+        let mut game = start.clone();
+        let mut last_move = None;
+        for (idx, play) in moves.iter().enumerate() {
+            let player = idx % 2;
+            let description = game.describe_move(play);
+            if game.make_move(play.clone(), player) == GameState::Invalid {
+                Err("invalid move in replay")?;
+            }
+            let mention = players[player].id(ctx).await.mention();
+            last_move = Some(format!("{} {}.", mention, description));
+        }
+        Ok((game, last_move))
+    }
+
+    /// stores the game, so it can be continued after a restart
+    async fn save(&self, ctx: &Context) -> CommandResult {
+        let game = SavedGame {
+            game: self.game_name.into(),
+            server: self.guild_id,
+            channel: self.board.channel_id,
+            board: self.board.id,
+            players: [self.players[0].id(ctx).await, self.players[1].id(ctx).await],
+            ai: self.players[1].is_ai(),
+            competitive: self.mode == GameMode::Competitive,
+            moves: encode_moves(&self.moves),
+            time_control: self.clock.control(),
+            remaining: self.clock.remaining(),
+            turn_start: chrono::Utc::now() - chrono::Duration::from_std(self.elapsed())?,
+        };
+        persist::save(&game)?;
+        Ok(())
+    }
+
+    /// how long the current move takes so far
+    fn elapsed(&self) -> Duration {
+        self.turn_offset + self.last_turn.elapsed()
+    }
+
+    /// when the player whose turn it is runs out of time
     fn forfeit(&self) -> bool {
-        self.time_left() == 0.0
+        self.clock.is_flagged(self.turn, self.elapsed())
+    }
+
+    /// the winner of a finished game, `None` if nobody won
+    pub fn winner(&self) -> Option<usize> {
+        match self.ending {
+            Some(Ending::Draw) => None,
+            Some(Ending::Resignation(p)) | Some(Ending::Flagged(p)) => Some(1 - p),
+            None => match self.game.status() {
+                GameState::Win(p) => Some(p),
+                _ => None,
+            },
+        }
+    }
+
+    /// waits for a move of the player or an action of any player
+    async fn next_event(
+        &self,
+        ctx: &Context,
+        player: UserId,
+        timeout: Duration,
+    ) -> Option<Event<Input>> {
+        let players = [self.players[0].id(ctx).await, self.players[1].id(ctx).await];
+
+        let reaction = self
+            .board
+            .await_reaction(ctx)
+            .timeout(timeout)
+            .filter(move |r| {
+                r.user_id.is_some_and(|user| players.contains(&user))
+                    && Action::of(&r.emoji).is_some()
+            });
+
+        tokio::select! {
+            play = self.input.receive_input(ctx, &self.board, &player, timeout) => play.ok().map(Event::Move),
+            Some(reaction) = reaction.next() => {
+                // remove the reaction, so it can be used again
+                reaction.delete(ctx).await.ok();
+                let user = reaction.user_id?;
+                let idx = if players[self.turn] == user { self.turn } else { 1 - self.turn };
+                Some(Event::Action(idx, Action::of(&reaction.emoji)?))
+            }
+        }
     }
 
-    /// how much time is left for the current move
-    fn time_left(&self) -> f64 {
-        (self.timeout - self.last_turn.elapsed().as_secs_f64()).max(0.0)
+    /// asks a player to confirm something
+    async fn confirm(
+        &self,
+        ctx: &Context,
+        player: usize,
+        title: &str,
+        body: &str,
+    ) -> CommandResult<bool> {
+        let user = self.players[player].id(ctx).await.to_user(ctx).await?;
+        confirm_dialog(ctx, &self.board, title, body, &user).await
+    }
+
+    /// handles an action of a player, returns whether the game ended
+    async fn act(&mut self, ctx: &Context, player: usize, action: Action) -> CommandResult<bool> {
+        let opponent = 1 - player;
+        let mentions = [
+            self.mention_player(ctx, player).await,
+            self.mention_player(ctx, opponent).await,
+        ];
+
+        match action {
+            Action::Resign => {
+                let body = format!("{}, do you really want to resign?", mentions[0]);
+                if self.confirm(ctx, player, "Resignation", &body).await? {
+                    self.ending = Some(Ending::Resignation(player));
+                    return Ok(true);
+                }
+            }
+            Action::Draw => {
+                // the AI always plays on
+                if self.players[opponent].is_ai() {
+                    return Ok(false);
+                }
+                let body = format!(
+                    "{} offers a draw, {} confirm to accept it.",
+                    mentions[0], mentions[1]
+                );
+                if self.confirm(ctx, opponent, "Draw Offer", &body).await? {
+                    self.ending = Some(Ending::Draw);
+                    return Ok(true);
+                }
+            }
+            Action::Takeback => {
+                // take back the last move of the player, and the answer of the opponent
+                let count = if self.turn == player { 2 } else { 1 };
+                if self.moves.len() < count {
+                    return Ok(false);
+                }
+
+                // the AI doesn't mind
+                if !self.players[opponent].is_ai() {
+                    let body = format!(
+                        "{} wants to take back their last move, {} confirm to allow it.",
+                        mentions[0], mentions[1]
+                    );
+                    if !self.confirm(ctx, opponent, "Takeback", &body).await? {
+                        return Ok(false);
+                    }
+                }
+
+                self.moves.truncate(self.moves.len() - count);
+                let (game, last_move) =
+                    Self::replay(ctx, &self.players, &self.start, &self.moves).await?;
+                self.game = game;
+                self.last_move = last_move;
+                self.turn = player;
+                self.last_turn = Instant::now();
+                self.turn_offset = Duration::ZERO;
+                self.drawn_moves = None;
+                self.save(ctx).await?;
+            }
+        }
+        Ok(false)
+    }
+
+    /// how much time a player has left, the clock of the other player isn't running
+    fn time_left(&self, player: usize) -> Duration {
+        let elapsed = if player == self.turn {
+            self.elapsed()
+        } else {
+            Duration::ZERO
+        };
+        self.clock.time_left(player, elapsed)
+    }
+
+    /// how often the board is updated while waiting for a move
+    fn refresh_interval(&self) -> Duration {
+        match self.clock.control() {
+            TimeControl::Correspondence(_) => Duration::from_secs(600),
+            _ => Duration::from_secs(10),
+        }
     }
 
     /// returns a Mention of the player with the index
@@ -179,31 +537,93 @@ impl<Input: 'static + Clone, G: PvpGame<Input> + Send + Sync> GameRunner<Input,
 
         let countdown = "<a:_:808040888235589772> <a:_:808040929515667526>";
 
-        let status = match self.game.status() {
-            _ if self.forfeit() => format!(
-                "{} won by inactivity of {}.",
-                mentions[1 - self.turn],
-                mentions[self.turn],
-            ),
-            GameState::Win(p) => format!("{} won!", mentions[p]),
-            GameState::Tie => String::from("It's a tie!"),
-            _ => format!(
-                "{}({}) plays next.\nTime left: {} seconds. (updated every once in a while)",
-                mentions[self.turn].mention(),
-                G::figures()[self.turn],
-                self.time_left() as u64
-            ),
+        let renderer = self.style.renderer();
+
+        let status = match self.ending {
+            Some(Ending::Draw) => String::from("The players agreed to a draw."),
+            Some(Ending::Resignation(p)) => {
+                format!("{} won, {} resigned.", mentions[1 - p], mentions[p])
+            }
+            Some(Ending::Flagged(p)) => match self.clock.control() {
+                TimeControl::Fischer { .. } => format!(
+                    "{} won on time, {} ran out of time.",
+                    mentions[1 - p],
+                    mentions[p],
+                ),
+                _ => format!("{} won by inactivity of {}.", mentions[1 - p], mentions[p]),
+            },
+            None => match self.game.status() {
+                GameState::Win(p) => format!("{} won!", mentions[p]),
+                GameState::Tie => String::from("It's a tie!"),
+                _ => {
+                    let clocks = (0..2)
+                        .map(|p| {
+                            let time = format_duration(self.time_left(p));
+                            format!("{}: `{}`", mentions[p], time)
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    let actions = Action::ALL
+                        .iter()
+                        .map(|action| action.emoji())
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    format!(
+                        "{}({}) plays next.\nTime control: {}\n{}\n{} offer a draw, take back a move or resign.",
+                        mentions[self.turn].mention(),
+                        renderer.piece(&G::pieces(), self.turn),
+                        self.clock.control().describe(),
+                        clocks,
+                        actions
+                    )
+                }
+            },
         };
 
-        let board = self.game.draw();
+        let mut fields = Vec::new();
+        if self.style != Style::Image {
+            let mut board = self.game.draw(renderer);
+            if self.style == Style::Ascii {
+                board = format!("```\n{}\n```", board);
+            }
+            fields.push(("Board", board));
+        }
+        if let Some(last_move) = self.last_move.clone() {
+            fields.push(("Last Move", last_move));
+        }
+        fields.push(("Status", status));
+
         let desc = self.description;
+        let fill = move |e: CreateEmbed| {
+            let fields = fields.into_iter().map(|(name, value)| (name, value, false));
+            e.title(G::title()).description(desc).fields(fields)
+        };
+
+        let mut image_url = None;
+        if self.style == Style::Image {
+            if self.drawn_moves != Some(self.moves.len()) {
+                // the board changed, so the message needs a new image
+                let board = self.game.highlighted_board(self.moves.last());
+                let png = image::render(&board, &G::pieces())?;
+                let file = CreateAttachment::bytes(png, "board.png");
+                self.board
+                    .eattach(ctx, file, |e| fill(e).attachment("board.png"))
+                    .await?;
+                self.drawn_moves = Some(self.moves.len());
+                return Ok(());
+            }
+            image_url = self
+                .board
+                .embeds
+                .iter()
+                .find_map(|e| e.image.as_ref())
+                .map(|image| image.url.clone());
+        }
 
         self.board
-            .eedit(ctx, |e| {
-                e.title(G::title());
-                e.description(desc);
-                e.field("Board", board, false);
-                e.field("Status", status, false)
+            .eedit(ctx, |e| match image_url {
+                Some(url) => fill(e).image(url),
+                None => fill(e),
             })
             .await?;
         Ok(())
@@ -211,26 +631,52 @@ impl<Input: 'static + Clone, G: PvpGame<Input> + Send + Sync> GameRunner<Input,
 
     /// runs the game
     pub async fn run(&mut self, ctx: &Context) -> CommandResult {
+        for action in Action::ALL.iter() {
+            let emoji = ReactionType::Unicode(action.emoji().into());
+            self.board.react(ctx, emoji).await?;
+        }
+
         'game: loop {
             self.last_turn = Instant::now();
+            if self.game.status().is_finished() {
+                break;
+            }
+            self.save(ctx).await?;
+
             let play = loop {
                 self.draw(ctx).await?;
 
                 if self.forfeit() {
+                    self.ending = Some(Ending::Flagged(self.turn));
                     break 'game;
                 }
 
-                let timeout = Duration::from_secs_f64(self.time_left().min(10.0));
-                let play = match &mut self.players[self.turn] {
-                    Player::Person(id) => tryc!(G::input()
-                        .receive_input(ctx, &self.board, id, timeout)
-                        .await
-                        .ok()),
-                    Player::Ai(ai) => ai.make_move(&self.game, self.turn),
+                let timeout = self.time_left(self.turn).min(self.refresh_interval());
+                let event = match &mut self.players[self.turn] {
+                    Player::Person(id) => {
+                        let id = *id;
+                        tryc!(self.next_event(ctx, id, timeout).await)
+                    }
+                    Player::Ai(ai) => Event::Move(ai.make_move(&self.game, self.turn)),
                 };
 
+                let play = match event {
+                    Event::Move(play) => play,
+                    Event::Action(player, action) => {
+                        if self.act(ctx, player, action).await? {
+                            break 'game;
+                        }
+                        continue;
+                    }
+                };
+
+                let description = self.game.describe_move(&play);
                 let state = self.game.make_move(play.clone(), self.turn);
                 if state != GameState::Invalid {
+                    self.clock.finish_move(self.turn, self.elapsed());
+                    self.turn_offset = Duration::ZERO;
+                    let player = self.mention_player(ctx, self.turn).await;
+                    self.last_move = Some(format!("{} {}.", player, description));
                     self.moves.push(play);
                     break state;
                 }
@@ -252,23 +698,26 @@ impl<Input: 'static + Clone, G: PvpGame<Input> + Send + Sync> GameRunner<Input,
             }
         }
 
+        persist::delete(self.board.id)?;
         self.draw(ctx).await?;
 
+        // nobody can make a move anymore
+        self.board
+            .edit(ctx, EditMessage::new().components(Vec::new()))
+            .await?;
+        self.board.delete_reactions(ctx).await.ok();
+
         if self.mode == GameMode::Competitive {
-            let winner = match self.game.status() {
-                GameState::Win(p) => Some(p),
-                _ if self.forfeit() => Some(1 - self.turn),
-                _ => None,
-            };
+            let winner = self.winner();
 
             let mut players = Vec::new();
             for p in self.players.iter() {
-                players.push(*p.id(ctx).await.as_u64());
+                players.push(p.id(ctx).await.get());
             }
 
-            // TODO
-            // log_game(self.game_name, server, &players, &self.moves, winner)?;
-            elo::process_game(self.game_name, self.guild_id, &players, winner)?;
+            let moves = encode_moves(&self.moves);
+            let game = log_game(self.game_name, self.guild_id, &players, &moves, winner)?;
+            elo::process_game(self.game_name, self.guild_id, &players, winner, game)?;
         }
 
         Ok(())
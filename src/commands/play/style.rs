@@ -0,0 +1,99 @@
+use super::*;
+
+#[group]
+#[help_available]
+#[prefix = "style"]
+#[commands(user_style, server_style)]
+pub struct BoardStyle;
+
+fn create_table() -> Result<()> {
+    db()?.execute(
+        "CREATE TABLE IF NOT EXISTS board_style (scope TEXT, id TEXT, style TEXT);",
+        params!(),
+    )?;
+    Ok(())
+}
+
+fn get(scope: &str, id: u64) -> Result<Option<Style>> {
+    create_table()?;
+    let style = db()?
+        .query_row(
+            "SELECT style FROM board_style WHERE scope = ?1 AND id = ?2",
+            params!(scope, format!("{}", id)),
+            |row| row.get::<_, String>(0),
+        )
+        .ok()
+        .and_then(|style| style.parse().ok());
+    Ok(style)
+}
+
+fn set(scope: &str, id: u64, style: Style) -> Result<()> {
+    create_table()?;
+    let id = format!("{}", id);
+    let db = db()?;
+
+    let affected = db.execute(
+        "UPDATE board_style SET style = ?3 WHERE scope = ?1 AND id = ?2;",
+        params!(scope, id, style.as_str()),
+    )?;
+
+    if affected == 0 {
+        db.execute(
+            "INSERT INTO board_style (scope, id, style) VALUES (?1, ?2, ?3);",
+            params!(scope, id, style.as_str()),
+        )?;
+    }
+    Ok(())
+}
+
+/// The style a game is drawn with.
+///
+/// The preferences of the players come first, then the one of the server.
+pub fn board_style(server: u64, players: &[UserId]) -> Result<Style> {
+    for player in players.iter() {
+        if let Some(style) = get("user", player.get())? {
+            return Ok(style);
+        }
+    }
+    Ok(get("server", server)?.unwrap_or_default())
+}
+
+async fn show_or_set(ctx: &Context, msg: &Message, scope: &str, id: u64) -> CommandResult {
+    let style = match msg.args().single::<String>() {
+        Ok(style) => {
+            let style = style.parse::<Style>()?;
+            set(scope, id, style)?;
+            style
+        }
+        Err(_) => get(scope, id)?.unwrap_or_default(),
+    };
+
+    msg.ereply(ctx, |e| {
+        e.title("Board Style").description(format!(
+            "Boards for this {} are drawn with `{}`.",
+            scope,
+            style.as_str()
+        ))
+    })
+    .await?;
+    Ok(())
+}
+
+#[command("user")]
+#[description = "Shows or sets how game boards are drawn for you. `ascii` draws the board in a code block, which works better with screen readers and on some clients. `image` attaches a picture of the board, with the last move and the winning line marked."]
+#[usage = "[emoji | ascii | image]"]
+#[example = "ascii"]
+async fn user_style(ctx: &Context, msg: &Message) -> CommandResult {
+    show_or_set(ctx, msg, "user", msg.author.id.get()).await
+}
+
+#[command("server")]
+#[only_in(guilds)]
+#[required_permissions("MANAGE_GUILD")]
+#[description = "Shows or sets how game boards are drawn on this server, for players that didn't choose a style themselves."]
+#[usage = "[emoji | ascii | image]"]
+#[example = "ascii"]
+async fn server_style(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = msg.guild_id.ok_or("not sent in a guild")?.get();
+    show_or_set(ctx, msg, "server", server).await
+}
@@ -0,0 +1,316 @@
+use super::*;
+use oxidized::game::tournament::*;
+use std::collections::HashMap;
+
+#[group]
+#[help_available]
+#[only_in(guilds)]
+#[checks(GameChannel)]
+#[prefix = "tournament"]
+#[commands(create, start, standings, cancel)]
+pub struct Tournaments;
+
+const SIGN_UP: &str = "✅";
+
+/// How often a game of a pairing is played again after it failed, before the tournament is
+/// cancelled
+const REPLAYS: usize = 2;
+
+/// A tournament of a channel, players sign up until it is started
+struct Entry {
+    game: &'static str,
+    format: Format,
+    time_control: TimeControl,
+    /// the message players react to for signing up
+    sign_up: Message,
+    players: Vec<UserId>,
+    tournament: Option<Tournament>,
+}
+
+lazy_static! {
+    static ref TOURNAMENTS: Mutex<HashMap<ChannelId, Entry>> = Mutex::new(HashMap::new());
+}
+
+#[command]
+#[required_permissions("MANAGE_GUILD")]
+#[description = "Creates a tournament in this channel, players sign up by reacting to the announcement. Swiss tournaments pair players with the same score, in round robin tournaments everybody plays everybody and in knockout tournaments the loser of a game is out."]
+#[usage = "<game> <swiss | roundrobin | knockout> [60 | 5+3 | 24h]"]
+#[example = "connect4 swiss 5+3"]
+async fn create(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = msg.guild_id.ok_or("not sent in a guild")?.get();
+
+    let mut args = msg.args();
+    let game = args.single::<String>()?;
+    let game = game_names()
+        .into_iter()
+        .find(|&name| name == game)
+        .ok_or("unknown game")?;
+    let format = args.single::<String>()?.parse::<Format>()?;
+    let time_control = match args.single::<String>() {
+        Ok(control) => control.parse()?,
+        Err(_) => config::game_timeout(server)?
+            .unwrap_or_else(|| TimeControl::PerMove(Duration::from_secs(60))),
+    };
+
+    let mut tournaments = TOURNAMENTS.lock().await;
+    if let Some(Entry {
+        tournament: Some(tournament),
+        ..
+    }) = tournaments.get(&msg.channel_id)
+    {
+        if !tournament.is_finished() {
+            return msg
+                .err_reply(ctx, "There already is a tournament in this channel.")
+                .await;
+        }
+    }
+
+    let sign_up = msg
+        .ereply(ctx, |e| {
+            e.title("Tournament").description(format!(
+                "A {} tournament of {} with {} is about to start.\nReact with {} to take part.",
+                format.as_str(),
+                game,
+                time_control.describe(),
+                SIGN_UP
+            ))
+        })
+        .await?;
+    sign_up
+        .react(ctx, ReactionType::Unicode(SIGN_UP.into()))
+        .await?;
+
+    let entry = Entry {
+        game,
+        format,
+        time_control,
+        sign_up,
+        players: Vec::new(),
+        tournament: None,
+    };
+    tournaments.insert(msg.channel_id, entry);
+    Ok(())
+}
+
+#[command]
+#[required_permissions("MANAGE_GUILD")]
+#[description = "Closes the sign up and plays the tournament of this channel, round by round."]
+async fn start(ctx: &Context, msg: &Message) -> CommandResult {
+    let server = msg.guild_id.ok_or("not sent in a guild")?.get();
+
+    let (game, time_control) = {
+        let mut tournaments = TOURNAMENTS.lock().await;
+        let entry = match tournaments.get_mut(&msg.channel_id) {
+            Some(entry) if entry.tournament.is_none() => entry,
+            _ => {
+                return msg
+                    .err_reply(ctx, "There is no tournament to start in this channel.")
+                    .await
+            }
+        };
+
+        let users = entry
+            .sign_up
+            .reaction_users(ctx, ReactionType::Unicode(SIGN_UP.into()), Some(100), None)
+            .await?;
+        let mut players = Vec::new();
+        for user in users.into_iter().filter(|user| !user.bot) {
+            let rating = elo::get(server, user.id.get(), entry.game)?;
+            players.push((user.id, rating));
+        }
+
+        if players.len() < 2 {
+            return msg
+                .err_reply(ctx, "A tournament needs at least two players.")
+                .await;
+        }
+
+        // the best rated player is the first seed
+        players.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        entry.players = players.into_iter().map(|(id, _)| id).collect();
+        entry.tournament = Some(Tournament::new(entry.format, entry.players.len()));
+        (entry.game, entry.time_control)
+    };
+
+    loop {
+        let (round, pairings, players) = {
+            let mut tournaments = TOURNAMENTS.lock().await;
+            let entry = match tournaments.get_mut(&msg.channel_id) {
+                Some(entry) => entry,
+                // the tournament was cancelled
+                None => return Ok(()),
+            };
+            let tournament = entry.tournament.as_mut().ok_or("no tournament")?;
+            let round = match tournament.next_round() {
+                Some(round) => round,
+                None => break,
+            };
+            let pairings = round
+                .map(|idx| (idx, tournament.pairings()[idx].clone()))
+                .collect::<Vec<_>>();
+            (tournament.round(), pairings, entry.players.clone())
+        };
+
+        let lines = pairings
+            .iter()
+            .map(|(_, p)| match p.second {
+                Some(second) => format!(
+                    "{} vs {}",
+                    players[p.first].mention(),
+                    players[second].mention()
+                ),
+                None => format!("{} has a bye", players[p.first].mention()),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        msg.ereply(ctx, |e| {
+            e.title(format!("Tournament Round {}", round))
+                .description(lines)
+        })
+        .await?;
+
+        let mut pending = pairings
+            .into_iter()
+            .filter_map(|(idx, p)| Some((idx, [players[p.first], players[p.second?]])))
+            .collect::<Vec<_>>();
+        let mut outcomes = Vec::new();
+        for replay in 0..=REPLAYS {
+            // all games of a round are played at the same time
+            let mut games = Vec::new();
+            for (idx, pairing) in pending {
+                let ctx = ctx.clone();
+                let prompt = msg.clone();
+                let game = tokio::spawn(async move {
+                    play_pairing(&ctx, &prompt, game, pairing, time_control).await
+                });
+                games.push((idx, pairing, game));
+            }
+
+            let mut failed = Vec::new();
+            for (idx, pairing, game) in games {
+                // a game that panicked only fails its own pairing
+                let result = match game.await {
+                    Ok(result) => result,
+                    Err(why) => Err(why.into()),
+                };
+                let outcome = match result {
+                    Ok(Some(0)) => Outcome::Win,
+                    Ok(Some(_)) => Outcome::Loss,
+                    Ok(None) => Outcome::Draw,
+                    Err(why) => {
+                        tracing::info!("Tournament game failed: {}", why);
+                        failed.push((idx, pairing));
+                        continue;
+                    }
+                };
+                outcomes.push((idx, outcome));
+            }
+
+            if failed.is_empty() {
+                break;
+            }
+            let lines = failed
+                .iter()
+                .map(|(_, [first, second])| format!("{} vs {}", first.mention(), second.mention()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            if replay == REPLAYS {
+                TOURNAMENTS.lock().await.remove(&msg.channel_id);
+                let error = format!(
+                    "These games failed again, so the tournament is cancelled:\n{}",
+                    lines
+                );
+                return msg.err_reply(ctx, &error).await;
+            }
+            msg.ereply(ctx, |e| {
+                e.title(format!("Tournament Round {}", round))
+                    .description(format!(
+                        "These games failed and are played again:\n{}",
+                        lines
+                    ))
+            })
+            .await?;
+            pending = failed;
+        }
+
+        {
+            let mut tournaments = TOURNAMENTS.lock().await;
+            let entry = match tournaments.get_mut(&msg.channel_id) {
+                Some(entry) => entry,
+                None => return Ok(()),
+            };
+            let tournament = entry.tournament.as_mut().ok_or("no tournament")?;
+            for (idx, outcome) in outcomes {
+                tournament.record(idx, outcome);
+            }
+        }
+
+        show_standings(ctx, msg).await?;
+    }
+
+    msg.ereply(ctx, |e| {
+        e.title("Tournament")
+            .description("The tournament is over, congratulations to the winners!")
+    })
+    .await?;
+    Ok(())
+}
+
+#[command]
+#[description = "Shows the standings of the tournament in this channel. Ties are broken by the Buchholz score (sum of the scores of the opponents), then by the Sonneborn-Berger score (sum of the scores of the beaten opponents and half of the drawn ones)."]
+async fn standings(ctx: &Context, msg: &Message) -> CommandResult {
+    show_standings(ctx, msg).await
+}
+
+#[command]
+#[required_permissions("MANAGE_GUILD")]
+#[description = "Cancels the tournament of this channel, games that already started are still played."]
+async fn cancel(ctx: &Context, msg: &Message) -> CommandResult {
+    if TOURNAMENTS.lock().await.remove(&msg.channel_id).is_none() {
+        return msg
+            .err_reply(ctx, "There is no tournament in this channel.")
+            .await;
+    }
+    msg.ereply(ctx, |e| {
+        e.title("Tournament")
+            .description("The tournament was cancelled.")
+    })
+    .await?;
+    Ok(())
+}
+
+async fn show_standings(ctx: &Context, msg: &Message) -> CommandResult {
+    let (standings, players, title) = {
+        let tournaments = TOURNAMENTS.lock().await;
+        let entry = tournaments
+            .get(&msg.channel_id)
+            .ok_or("no tournament in this channel")?;
+        let tournament = entry.tournament.as_ref().ok_or("tournament not started")?;
+        let title = format!(
+            "Standings after Round {} of {}",
+            tournament.round(),
+            tournament.rounds()
+        );
+        (tournament.standings(), entry.players.clone(), title)
+    };
+
+    let lines = standings
+        .iter()
+        .enumerate()
+        .map(|(idx, s)| {
+            format!(
+                "`{} {:>4.1}  Bh {:>4.1}  SB {:>5.2}` {}\n",
+                rank_string(idx + 1),
+                s.score,
+                s.buchholz,
+                s.sonneborn_berger,
+                players[s.player].mention()
+            )
+        })
+        .collect::<Vec<_>>();
+    let fields = split_into_fields(&lines, "Nobody takes part.");
+
+    msg.ereply(ctx, |e| e.title(title).description(&fields[0]))
+        .await?;
+    Ok(())
+}
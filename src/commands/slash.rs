@@ -0,0 +1,246 @@
+//! Slash commands, which are translated into the prefix commands they stand for.
+//!
+//! The response to a slash command is the equivalent prefix command, which is then dispatched by
+//! the framework as if a user had sent it, so buckets, checks and permissions apply as well.
+
+use super::brainfuck::{program_names, BRAINFUCK_GROUP};
+use super::play::{GAMES_GROUP, LEADERBOARD_GROUP};
+use crate::prelude::*;
+use crate::ser::*;
+use crate::FrameworkContainer;
+
+/// Discord only shows this many autocomplete choices
+const MAX_CHOICES: usize = 25;
+
+fn string_option(name: &str, description: &str, required: bool) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, name, description).required(required)
+}
+
+fn game_option() -> CreateCommandOption {
+    string_option("game", "The game to play", true).set_autocomplete(true)
+}
+
+fn program_option(required: bool) -> CreateCommandOption {
+    string_option("name", "The name of the stored program", required).set_autocomplete(true)
+}
+
+fn sub_command(name: &str, description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::SubCommand, name, description)
+}
+
+pub fn create_commands() -> Vec<CreateCommand> {
+    let opponent = CreateCommandOption::new(
+        CommandOptionType::User,
+        "opponent",
+        "The person you want to play against, tag the bot to play against the AI",
+    )
+    .required(true);
+
+    let input = || string_option("input", "The input of the program", false);
+
+    vec![
+        CreateCommand::new("play")
+            .description("Starts a game")
+            .dm_permission(false)
+            .add_option(game_option())
+            .add_option(opponent),
+        CreateCommand::new("leaderboard")
+            .description("Shows the leaderboard of a game on this server")
+            .dm_permission(false)
+            .add_option(game_option()),
+        CreateCommand::new("brainfuck")
+            .description("Runs and stores brainfuck programs")
+            .add_option(
+                sub_command("exec", "Executes a brainfuck program")
+                    .add_sub_option(string_option("program", "The brainfuck code", true))
+                    .add_sub_option(input()),
+            )
+            .add_option(
+                sub_command("run", "Runs a stored brainfuck program")
+                    .add_sub_option(program_option(true))
+                    .add_sub_option(input()),
+            )
+            .add_option(
+                sub_command("store", "Stores a brainfuck program")
+                    .add_sub_option(string_option("name", "The name of the program", true))
+                    .add_sub_option(string_option("program", "The brainfuck code", true)),
+            )
+            .add_option(
+                sub_command(
+                    "load",
+                    "Shows a stored brainfuck program or lists all of them",
+                )
+                .add_sub_option(program_option(false)),
+            ),
+    ]
+}
+
+/// A prefix command with its arguments
+struct Translation {
+    group: &'static CommandGroup,
+    command: String,
+    args: Vec<String>,
+    mentions: Vec<User>,
+}
+
+fn string<'a>(options: &[ResolvedOption<'a>], name: &str) -> Option<&'a str> {
+    options.iter().find_map(|option| match option.value {
+        ResolvedValue::String(value) if option.name == name => Some(value),
+        _ => None,
+    })
+}
+
+/// Puts arguments with spaces in quotes, so that they stay one argument
+fn quote(arg: &str) -> String {
+    if arg.contains(char::is_whitespace) {
+        format!("\"{}\"", arg)
+    } else {
+        arg.into()
+    }
+}
+
+fn translate(interaction: &CommandInteraction) -> Result<Translation, &'static str> {
+    let options = interaction.data.options();
+
+    match interaction.data.name.as_str() {
+        "play" => {
+            let opponent = options
+                .iter()
+                .find_map(|option| match option.value {
+                    ResolvedValue::User(user, _) => Some(user.clone()),
+                    _ => None,
+                })
+                .ok_or("no opponent")?;
+            Ok(Translation {
+                group: &GAMES_GROUP,
+                command: string(&options, "game").ok_or("no game")?.into(),
+                args: vec![opponent.mention().to_string()],
+                mentions: vec![opponent],
+            })
+        }
+        "leaderboard" => Ok(Translation {
+            group: &LEADERBOARD_GROUP,
+            command: string(&options, "game").ok_or("no game")?.into(),
+            args: Vec::new(),
+            mentions: Vec::new(),
+        }),
+        "brainfuck" => {
+            let (sub, options) = options
+                .iter()
+                .find_map(|option| match &option.value {
+                    ResolvedValue::SubCommand(options) => Some((option.name, options)),
+                    _ => None,
+                })
+                .ok_or("no subcommand")?;
+
+            let (command, names): (_, &[_]) = match sub {
+                "exec" => ("brainfuck", &["program"]),
+                "run" => ("run", &["name"]),
+                "store" => ("store", &["name", "program"]),
+                "load" => ("load", &["name"]),
+                _ => return Err("unknown subcommand"),
+            };
+
+            let mut args = names
+                .iter()
+                .filter_map(|name| string(options, name))
+                .map(quote)
+                .collect::<Vec<_>>();
+
+            // the input is the rest of the message, so it doesn't need quotes
+            args.extend(string(options, "input").map(String::from));
+
+            Ok(Translation {
+                group: &BRAINFUCK_GROUP,
+                command: command.into(),
+                args,
+                mentions: Vec::new(),
+            })
+        }
+        _ => Err("unknown command"),
+    }
+}
+
+/// Runs the prefix command behind a slash command
+pub async fn run(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let translation = translate(interaction)?;
+    let group = translation.group;
+    let name = translation.command.as_str();
+
+    let known = group
+        .options
+        .commands
+        .iter()
+        .any(|c| c.options.names.contains(&name));
+    if !known {
+        Err("unknown command")?;
+    }
+
+    let mut words = Vec::new();
+    words.extend(group.options.prefixes.first().map(|p| p.to_string()));
+    words.push(name.into());
+    words.extend(translation.args);
+    let content = format!("{}{}", *PREFIX, words.join(" "));
+
+    let response = CreateInteractionResponseMessage::new().content(&content);
+    interaction
+        .create_response(ctx, CreateInteractionResponse::Message(response))
+        .await?;
+
+    // the response takes the place of the message that invokes the command
+    let mut msg = interaction.get_response(ctx).await?;
+    msg.author = interaction.user.clone();
+    msg.member = interaction
+        .member
+        .clone()
+        .map(|member| Box::new(PartialMember::from(*member)));
+    msg.guild_id = interaction.guild_id;
+    msg.mentions = translation.mentions;
+    msg.content = content;
+    // the response is sent through the webhook of the interaction, the framework ignores webhooks
+    msg.webhook_id = None;
+
+    let framework = ctx
+        .data
+        .read()
+        .await
+        .get::<FrameworkContainer>()
+        .cloned()
+        .ok_or("no framework")?;
+    framework
+        .dispatch(ctx.clone(), FullEvent::Message { new_message: msg })
+        .await;
+    Ok(())
+}
+
+/// Suggests game names and the names of stored brainfuck programs
+pub async fn autocomplete(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let option = interaction
+        .data
+        .autocomplete()
+        .ok_or("no option to complete")?;
+
+    let names = match option.name {
+        "game" => GAMES_GROUP
+            .options
+            .commands
+            .iter()
+            .flat_map(|c| c.options.names.iter().map(|n| n.to_string()))
+            .collect(),
+        "name" => program_names(interaction.user.id)?,
+        _ => Vec::new(),
+    };
+
+    let choices = names
+        .into_iter()
+        .filter(|name| name.starts_with(option.value))
+        .take(MAX_CHOICES)
+        .map(|name| AutocompleteChoice::new(name.clone(), name))
+        .collect();
+
+    let response = CreateAutocompleteResponse::new().set_choices(choices);
+    interaction
+        .create_response(ctx, CreateInteractionResponse::Autocomplete(response))
+        .await?;
+    Ok(())
+}
@@ -17,12 +17,12 @@ async fn random(ctx: &Context, msg: &Message) -> CommandResult {
 
     let word = words.choose(&mut rand::thread_rng());
     msg.ereply(ctx, |e| {
-        e.title("Random element");
+        let e = e.title("Random element");
         if let Some(word) = word {
             e.description(format!("{} has been chosen.", word))
         } else {
-            e.colour(Colour::RED);
-            e.description("Please specify a list of words.")
+            e.colour(Colour::RED)
+                .description("Please specify a list of words.")
         }
     })
     .await?;
@@ -39,7 +39,9 @@ async fn track(ctx: &Context, msg: &Message) -> CommandResult {
         let y = rng.gen::<f32>() * 40.0;
         format!("<https://www.google.com/maps/@{:.7}:{:.7},11z>", x, y)
     };
-    msg.author.dm(ctx, |f| f.content(url)).await?;
+    msg.author
+        .dm(ctx, CreateMessage::new().content(url))
+        .await?;
     Ok(())
 }
 
@@ -47,29 +49,28 @@ async fn track(ctx: &Context, msg: &Message) -> CommandResult {
 #[description = "Displays info about a server"]
 #[only_in(guilds)]
 async fn serverinfo(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = msg.guild(ctx).await.ok_or("no guild")?;
+    let guild = msg.guild(&ctx.cache).ok_or("no guild")?.clone();
     let inline = true;
     let display_roles = false;
 
     let channels = &guild.channels(ctx).await?;
 
     msg.ereply(ctx, |e| {
-        e.title(&guild.name);
+        let mut e = e.title(&guild.name);
         if let Some(desc) = &guild.description {
-            e.description(desc);
+            e = e.description(desc);
         }
         if let Some(icon) = guild.icon_url() {
-            e.thumbnail(icon);
+            e = e.thumbnail(icon);
         }
         if let Some(splash) = guild.splash_url() {
-            e.image(splash);
+            e = e.image(splash);
         }
 
-        e.field("Owner", guild.owner_id.mention(), inline);
+        e = e.field("Owner", guild.owner_id.mention().to_string(), inline);
         if let Some(url) = &guild.vanity_url_code {
-            e.field("Url", url, inline);
+            e = e.field("Url", url, inline);
         }
-        e.field("Region", &guild.region, inline);
 
         if display_roles {
             let mut roles = guild.roles.values().collect::<Vec<_>>();
@@ -79,7 +80,7 @@ async fn serverinfo(ctx: &Context, msg: &Message) -> CommandResult {
             for role in roles.iter() {
                 let mention = format!("{}\n", role.id.mention());
                 if roles_string.len() + mention.len() > 1000 {
-                    e.field(format!("Roles({})", role_fields), &roles_string, inline);
+                    e = e.field(format!("Roles({})", role_fields), &roles_string, inline);
                     roles_string.clear();
                     role_fields += 1;
                 }
@@ -91,32 +92,32 @@ async fn serverinfo(ctx: &Context, msg: &Message) -> CommandResult {
                 } else {
                     format!("Roles({})", role_fields)
                 };
-                e.field(title, roles_string, inline);
+                e = e.field(title, roles_string, inline);
             }
         }
 
-        e.field("Members", guild.member_count, inline);
-        e.field("Boosts", guild.premium_subscription_count, inline);
-        e.field("Emojis", guild.emojis.len(), inline);
-
-        e.field(
-            "Text Channels",
-            channels
-                .values()
-                .filter(|c| c.kind == ChannelType::Text)
-                .count(),
-            inline,
-        );
-        e.field(
-            "Voice Channels",
-            channels
-                .values()
-                .filter(|c| c.kind == ChannelType::Voice)
-                .count(),
-            inline,
-        );
-
-        e
+        let boosts = guild.premium_subscription_count.unwrap_or(0);
+        e.field("Members", guild.member_count.to_string(), inline)
+            .field("Boosts", boosts.to_string(), inline)
+            .field("Emojis", guild.emojis.len().to_string(), inline)
+            .field(
+                "Text Channels",
+                channels
+                    .values()
+                    .filter(|c| c.kind == ChannelType::Text)
+                    .count()
+                    .to_string(),
+                inline,
+            )
+            .field(
+                "Voice Channels",
+                channels
+                    .values()
+                    .filter(|c| c.kind == ChannelType::Voice)
+                    .count()
+                    .to_string(),
+                inline,
+            )
     })
     .await?;
     Ok(())
@@ -124,18 +125,16 @@ async fn serverinfo(ctx: &Context, msg: &Message) -> CommandResult {
 
 #[command]
 pub async fn serveremojis(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = msg.guild(ctx).await.ok_or("no guild")?;
-    let emojis = guild
+    let emojis = msg
+        .guild(&ctx.cache)
+        .ok_or("no guild")?
         .emojis
         .iter()
-        .map(|e| format!("{} ", e.1.mention()))
+        .map(|e| format!("{} ", e.1))
         .collect::<String>();
 
-    msg.ereply(ctx, |e| {
-        e.title("Emojis");
-        e.description(&emojis)
-    })
-    .await?;
+    msg.ereply(ctx, |e| e.title("Emojis").description(&emojis))
+        .await?;
 
     event!(tracing::Level::INFO, "{}", emojis);
     Ok(())
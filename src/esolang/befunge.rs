@@ -0,0 +1,251 @@
+//! Befunge-93, a two-dimensional stack based language. The program counter moves over a grid
+//! of 80×25 cells in one of four directions and the program can change itself with `p`.
+//!
+//! Taking a value from the empty stack gives zero and dividing by zero gives zero.
+//! `?` uses a random generator that always starts the same way, so programs always run the
+//! same way.
+
+use super::*;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 25;
+
+const RIGHT: (isize, isize) = (1, 0);
+const LEFT: (isize, isize) = (-1, 0);
+const UP: (isize, isize) = (0, -1);
+const DOWN: (isize, isize) = (0, 1);
+
+pub struct Befunge {
+    grid: Vec<[u8; WIDTH]>,
+    x: usize,
+    y: usize,
+    direction: (isize, isize),
+    /// whether the cells are pushed as characters, between two `"`
+    string_mode: bool,
+    stack: Vec<i64>,
+    /// bytes of a number that still have to be written
+    pending: VecDeque<u8>,
+    /// the number `&` is reading, once it read a digit
+    number: Option<i64>,
+    /// state of the random generator of `?`
+    random: u64,
+}
+
+impl Befunge {
+    pub fn new(source: &str) -> Result<Self, ParseError> {
+        let mut grid = vec![[b' '; WIDTH]; HEIGHT];
+        let (mut x, mut y) = (0, 0);
+        for (position, ch) in source.chars().enumerate() {
+            let message = match ch {
+                '\n' => {
+                    x = 0;
+                    y += 1;
+                    continue;
+                }
+                '\r' => continue,
+                _ if !ch.is_ascii() => "only ASCII characters are allowed",
+                _ if y >= HEIGHT => "the program is longer than 25 lines",
+                _ if x >= WIDTH => "the line is longer than 80 characters",
+                _ => {
+                    grid[y][x] = ch as u8;
+                    x += 1;
+                    continue;
+                }
+            };
+            return Err(ParseError { message, position });
+        }
+        Ok(Self {
+            grid,
+            x: 0,
+            y: 0,
+            direction: RIGHT,
+            string_mode: false,
+            stack: Vec::new(),
+            pending: VecDeque::new(),
+            number: None,
+            random: 0x2545_f491_4f6c_dd1d,
+        })
+    }
+
+    fn pop(&mut self) -> i64 {
+        self.stack.pop().unwrap_or(0)
+    }
+
+    fn push(&mut self, value: i64) -> Result<(), ExitCode> {
+        if self.stack.len() >= MAX_MEMORY {
+            return Err(ExitCode::OutOfMemory);
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    /// replaces the two top values by the result of an operation on them
+    fn binary(&mut self, operation: impl FnOnce(i64, i64) -> i64) -> Result<(), ExitCode> {
+        let b = self.pop();
+        let a = self.pop();
+        self.push(operation(a, b))
+    }
+
+    /// a position that is popped from the stack, if it's on the grid
+    fn position(&mut self) -> Option<(usize, usize)> {
+        let y = self.pop();
+        let x = self.pop();
+        let y = usize::try_from(y).ok().filter(|&y| y < HEIGHT)?;
+        let x = usize::try_from(x).ok().filter(|&x| x < WIDTH)?;
+        Some((x, y))
+    }
+
+    /// xorshift, which is good enough to pick a direction
+    fn random(&mut self) -> u64 {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 7;
+        self.random ^= self.random << 17;
+        self.random
+    }
+
+    /// moves the program counter one cell, it wraps around at the edges
+    fn advance(&mut self) {
+        let (dx, dy) = self.direction;
+        self.x = (self.x as isize + dx).rem_euclid(WIDTH as isize) as usize;
+        self.y = (self.y as isize + dy).rem_euclid(HEIGHT as isize) as usize;
+    }
+
+    fn execute(&mut self, input: &mut Option<Input>) -> Result<Option<Output>, ExitCode> {
+        let cell = self.grid[self.y][self.x];
+        if self.string_mode {
+            if cell == b'"' {
+                self.string_mode = false;
+            } else {
+                self.push(cell.into())?;
+            }
+            self.advance();
+            return Ok(None);
+        }
+        match cell {
+            b'0'..=b'9' => self.push((cell - b'0').into())?,
+            b'+' => self.binary(i64::wrapping_add)?,
+            b'-' => self.binary(i64::wrapping_sub)?,
+            b'*' => self.binary(i64::wrapping_mul)?,
+            b'/' => self.binary(|a, b| if b == 0 { 0 } else { a.wrapping_div(b) })?,
+            b'%' => self.binary(|a, b| if b == 0 { 0 } else { a.wrapping_rem(b) })?,
+            b'!' => {
+                let value = self.pop();
+                self.push((value == 0).into())?;
+            }
+            b'`' => self.binary(|a, b| (a > b).into())?,
+            b'>' => self.direction = RIGHT,
+            b'<' => self.direction = LEFT,
+            b'^' => self.direction = UP,
+            b'v' => self.direction = DOWN,
+            b'?' => self.direction = [RIGHT, LEFT, UP, DOWN][(self.random() >> 62) as usize],
+            b'_' => self.direction = if self.pop() == 0 { RIGHT } else { LEFT },
+            b'|' => self.direction = if self.pop() == 0 { DOWN } else { UP },
+            b'"' => self.string_mode = true,
+            b':' => {
+                let value = self.pop();
+                self.push(value)?;
+                self.push(value)?;
+            }
+            b'\\' => {
+                let b = self.pop();
+                let a = self.pop();
+                self.push(b)?;
+                self.push(a)?;
+            }
+            b'$' => {
+                self.pop();
+            }
+            b'.' => {
+                let value = self.pop();
+                self.pending.extend(format!("{} ", value).bytes());
+                self.advance();
+                return Ok(self.pending.pop_front().map(Output::Value));
+            }
+            b',' => {
+                let value = self.pop();
+                self.advance();
+                return Ok(Some(Output::Value(value as u8)));
+            }
+            b'#' => self.advance(),
+            b'p' => {
+                let position = self.position();
+                let value = self.pop();
+                if let Some((x, y)) = position {
+                    self.grid[y][x] = value as u8;
+                }
+            }
+            b'g' => {
+                let value = self.position().map_or(0, |(x, y)| self.grid[y][x]);
+                self.push(value.into())?;
+            }
+            b'&' => match input.take() {
+                None => return Ok(Some(Output::Starved)),
+                Some(Input::Byte(byte)) if byte.is_ascii_digit() => {
+                    let digit = i64::from(byte - b'0');
+                    let number = self.number.unwrap_or(0);
+                    self.number = Some(number.wrapping_mul(10).wrapping_add(digit));
+                    // the number might go on
+                    return Ok(None);
+                }
+                // everything in front of the number is skipped
+                Some(Input::Byte(_)) if self.number.is_none() => return Ok(None),
+                Some(Input::Byte(_)) | Some(Input::Eof) => {
+                    let number = self.number.take().unwrap_or(-1);
+                    self.push(number)?;
+                }
+            },
+            b'~' => match input.take() {
+                None => return Ok(Some(Output::Starved)),
+                Some(Input::Byte(byte)) => self.push(byte.into())?,
+                Some(Input::Eof) => self.push(-1)?,
+            },
+            b'@' => return Ok(Some(Output::Terminated)),
+            _ => {}
+        }
+        self.advance();
+        Ok(None)
+    }
+}
+
+impl Interpreter for Befunge {
+    fn step(&mut self, input: &mut Option<Input>) -> Option<Output> {
+        if let Some(byte) = self.pending.pop_front() {
+            return Some(Output::Value(byte));
+        }
+        match self.execute(input) {
+            Ok(output) => output,
+            Err(exit_code) => Some(Output::Error(exit_code)),
+        }
+    }
+}
+
+#[cfg(test)]
+fn run(program: &str, input: &str) -> (Vec<u8>, ExitCode) {
+    let mut progs = [Befunge::new(program).unwrap()];
+    let cancel = AtomicBool::new(false);
+    let execution = execute(
+        &mut progs,
+        input.as_bytes(),
+        Limit::Steps(10_000),
+        100,
+        &cancel,
+    );
+    (execution.output, execution.exit_code)
+}
+
+#[test]
+fn befunge_test() {
+    let hello = run("0\"!dlroW ,olleH\">:#,_@", "");
+    assert_eq!(hello, (b"Hello, World!".to_vec(), ExitCode::Success));
+    assert_eq!(run("&&+.@", "12 30").0, b"42 ");
+    assert_eq!(run("~:1+!#@_,", "cat").0, b"cat");
+    // the program writes the `@` that ends it
+    assert_eq!(run("\"@\"80p1.", "").0, b"1 ");
+    assert_eq!(run("v\n>5 0/.@", "").0, b"0 ");
+    assert_eq!(run(" ", "").1, ExitCode::OutOfSteps);
+
+    assert_eq!(Befunge::new(&"a\n".repeat(26)).err().unwrap().position, 50);
+    assert_eq!(Befunge::new(&"a".repeat(81)).err().unwrap().position, 80);
+}
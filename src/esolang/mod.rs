@@ -0,0 +1,387 @@
+//! Discord independent interpreters for esoteric languages.
+//!
+//! Every language implements `Interpreter`, so programs of different languages share the same
+//! limits and can be piped into each other. Brainfuck lives in its own module, Ook! is
+//! translated to brainfuck.
+
+pub mod befunge;
+pub mod ook;
+pub mod whitespace;
+
+use crate::brainfuck::{parse_dialect, Dialect, ProgContext};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::*;
+
+/// How many instructions are executed between two checks of the clock
+const CLOCK_INTERVAL: usize = 1 << 12;
+
+/// How long a run with a budget of steps may take at most, a big budget doesn't keep a
+/// worker busy forever
+const STEP_TIME_CAP: Duration = Duration::from_secs(30);
+
+/// Largest stack or heap a program can use
+pub const MAX_MEMORY: usize = 1 << 20;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ExitCode {
+    Success,
+    Timeout,
+    /// the pointer left the tape, only without wrapping or on an infinite tape
+    PointerOutOfBounds,
+    /// a cell went below zero or above its maximum, only without wrapping
+    CellOverflow,
+    /// the execution was stopped from the outside
+    Cancelled,
+    /// the instruction budget was used up
+    OutOfSteps,
+    /// a value was taken from an empty stack
+    StackUnderflow,
+    DivisionByZero,
+    /// the stack or heap grew too big
+    OutOfMemory,
+}
+
+/// How long programs may run
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Limit {
+    /// seconds of wall-clock time, which depends on how busy the host is
+    Time(f64),
+    /// an exact number of steps, so a program always ends the same way,
+    /// unless it still runs after `STEP_TIME_CAP`
+    Steps(usize),
+}
+
+/// A byte that is read by a program, or the end of the input
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Input {
+    Byte(u8),
+    Eof,
+}
+
+#[derive(Copy, Clone)]
+pub enum Output {
+    Starved,
+    Terminated,
+    Timeout,
+    Value(u8),
+    /// the program crashed
+    Error(ExitCode),
+}
+
+/// A program that can't be compiled
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct ParseError {
+    pub message: &'static str,
+    /// the character of the program that causes the error
+    pub position: usize,
+}
+
+impl ParseError {
+    /// the error with the part of the program around it, in a code block
+    pub fn excerpt(&self, source: &str) -> String {
+        format!(
+            "{} at column {}\n```\n{}\n```",
+            self.message,
+            self.position + 1,
+            excerpt(source, self.position)
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A line of the program around a position, with a caret below that position
+pub fn excerpt(source: &str, position: usize) -> String {
+    const WIDTH: usize = 40;
+    let source = source.chars().collect::<Vec<_>>();
+    let start = position.saturating_sub(WIDTH / 2);
+    let end = source.len().min(start + WIDTH);
+    let line = source[start.min(end)..end]
+        .iter()
+        .map(|&ch| if ch.is_whitespace() { ' ' } else { ch })
+        .collect::<String>();
+    format!("{}\n{}^", line, " ".repeat(position - start))
+}
+
+/// A compiled program of any language
+pub trait Interpreter: Send {
+    /// Executes a single instruction, returns when the program outputs, needs input or ends.
+    /// A program that needs input returns `Output::Starved` and executes the same instruction
+    /// again once the input is there.
+    fn step(&mut self, input: &mut Option<Input>) -> Option<Output>;
+
+    /// Steps that the last instruction took in addition to the first one,
+    /// for instructions that replace whole loops
+    fn take_extra_steps(&mut self) -> usize {
+        0
+    }
+
+    /// How many extra steps the next instruction may take at most, an instruction that would
+    /// take more stops early and continues when it is executed again
+    fn limit_extra_steps(&mut self, _steps: usize) {}
+
+    /// the highest memory cell that was used, for languages with a tape or heap
+    fn max_cell(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<I: Interpreter + ?Sized> Interpreter for Box<I> {
+    fn step(&mut self, input: &mut Option<Input>) -> Option<Output> {
+        (**self).step(input)
+    }
+
+    fn take_extra_steps(&mut self) -> usize {
+        (**self).take_extra_steps()
+    }
+
+    fn limit_extra_steps(&mut self, steps: usize) {
+        (**self).limit_extra_steps(steps)
+    }
+
+    fn max_cell(&self) -> Option<usize> {
+        (**self).max_cell()
+    }
+}
+
+/// The languages that programs can be written in
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Language {
+    Brainfuck,
+    Befunge,
+    Whitespace,
+    Ook,
+}
+
+impl Language {
+    pub const ALL: [Self; 4] = [Self::Brainfuck, Self::Befunge, Self::Whitespace, Self::Ook];
+
+    /// the name that is stored and used in flags
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Brainfuck => "brainfuck",
+            Self::Befunge => "befunge",
+            Self::Whitespace => "whitespace",
+            Self::Ook => "ook",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::Brainfuck => "Brainfuck",
+            Self::Befunge => "Befunge-93",
+            Self::Whitespace => "Whitespace",
+            Self::Ook => "Ook!",
+        }
+    }
+
+    /// the usual file extension of programs
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Brainfuck => "bf",
+            Self::Befunge => "b93",
+            Self::Whitespace => "ws",
+            Self::Ook => "ook",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|lang| lang.name() == name || lang.extension() == name)
+    }
+
+    /// Compiles a program, the dialect only changes brainfuck and Ook!
+    pub fn compile(
+        self,
+        source: &str,
+        dialect: Dialect,
+    ) -> Result<Box<dyn Interpreter>, ParseError> {
+        Ok(match self {
+            Self::Brainfuck => Box::new(ProgContext::with_dialect(
+                parse_dialect(source, dialect)?,
+                dialect,
+            )),
+            Self::Befunge => Box::new(befunge::Befunge::new(source)?),
+            Self::Whitespace => Box::new(whitespace::Whitespace::new(source)?),
+            Self::Ook => Box::new(ProgContext::with_dialect(
+                ook::parse(source, dialect)?,
+                dialect,
+            )),
+        })
+    }
+}
+
+/// When an execution has to stop
+struct Stop<'a> {
+    until: Option<Instant>,
+    budget: usize,
+    cancel: &'a AtomicBool,
+}
+
+/// Runs a program until it outputs, needs input or ends, or until it has to stop.
+/// `next_check` is the step after which the clock of this program is checked again.
+fn next_output<P: Interpreter>(
+    prog: &mut P,
+    input: &mut Option<Input>,
+    iter: &mut usize,
+    next_check: &mut usize,
+    stop: &Stop,
+) -> Output {
+    loop {
+        if *iter >= stop.budget {
+            return Output::Error(ExitCode::OutOfSteps);
+        }
+        if *iter >= *next_check {
+            *next_check = *iter + CLOCK_INTERVAL;
+            if stop.until.is_some_and(|until| Instant::now() >= until) {
+                return Output::Timeout;
+            }
+            if stop.cancel.load(Ordering::Relaxed) {
+                return Output::Error(ExitCode::Cancelled);
+            }
+        }
+        *iter += 1;
+        prog.limit_extra_steps(stop.budget - *iter);
+        let output = prog.step(input);
+        *iter += prog.take_extra_steps();
+        if let Some(output) = output {
+            return output;
+        }
+    }
+}
+
+/// Runs programs where each one reads the output of the one before, the first one reads
+/// the input of the user. The output of the last program is cut off after some bytes.
+/// Stops with `ExitCode::Cancelled` once the flag is set.
+pub fn execute<P: Interpreter>(
+    progs: &mut [P],
+    user_input: &[u8],
+    limit: Limit,
+    char_limit: usize,
+    cancel: &AtomicBool,
+) -> Execution {
+    assert!(!progs.is_empty());
+
+    let stop = match limit {
+        Limit::Time(seconds) => Stop {
+            until: Some(Instant::now() + Duration::from_secs_f64(seconds)),
+            budget: usize::MAX,
+            cancel,
+        },
+        Limit::Steps(budget) => Stop {
+            until: Some(Instant::now() + STEP_TIME_CAP),
+            budget,
+            cancel,
+        },
+    };
+    let mut pid = progs.len() - 1;
+    let mut iter = 0;
+    let mut next_checks = vec![0; progs.len()];
+
+    let mut user_input = user_input.iter();
+    let mut prog_input = vec![None; progs.len()];
+
+    let mut output = Vec::new();
+    let mut dropped = 0;
+
+    let exit_code = loop {
+        let out = next_output(
+            &mut progs[pid],
+            &mut prog_input[pid],
+            &mut iter,
+            &mut next_checks[pid],
+            &stop,
+        );
+        match out {
+            Output::Starved => {
+                if pid == 0 {
+                    prog_input[pid] = Some(match user_input.next() {
+                        Some(&byte) => Input::Byte(byte),
+                        None => Input::Eof,
+                    });
+                } else {
+                    pid -= 1;
+                }
+            }
+            Output::Terminated => {
+                if pid == progs.len() - 1 {
+                    break ExitCode::Success;
+                } else {
+                    prog_input[pid + 1] = Some(Input::Eof);
+                    pid += 1;
+                }
+            }
+            Output::Timeout => break ExitCode::Timeout,
+            Output::Error(exit_code) => break exit_code,
+            Output::Value(val) => {
+                if pid == progs.len() - 1 {
+                    if output.len() < char_limit {
+                        output.push(val);
+                    } else {
+                        dropped += 1;
+                    }
+                } else {
+                    prog_input[pid + 1] = Some(Input::Byte(val));
+                    pid += 1;
+                }
+            }
+        }
+    };
+
+    Execution {
+        steps: iter,
+        output,
+        dropped,
+        exit_code,
+        program: pid,
+        max_cell: progs.iter().filter_map(Interpreter::max_cell).max(),
+    }
+}
+
+/// The result of running programs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Execution {
+    /// number of executed instructions of all programs, a brainfuck scan counts every cell
+    /// it passes
+    pub steps: usize,
+    pub output: Vec<u8>,
+    /// how many bytes of the output were cut off
+    pub dropped: usize,
+    pub exit_code: ExitCode,
+    /// index of the program that ran last, the one that crashed or ran out of time
+    pub program: usize,
+    /// the highest cell used by any program, `None` if no program has a tape or heap
+    pub max_cell: Option<usize>,
+}
+
+#[test]
+fn pipe_test() {
+    let programs = [
+        (Language::Befunge, "\"olleh\",,,,,@"),
+        (Language::Brainfuck, ",[.,]"),
+        (
+            Language::Ook,
+            "Ook. Ook! Ook! Ook? Ook! Ook. Ook. Ook! Ook? Ook!",
+        ),
+    ];
+    let mut progs = programs
+        .iter()
+        .map(|&(lang, source)| lang.compile(source, Dialect::default()).unwrap())
+        .collect::<Vec<_>>();
+    let cancel = AtomicBool::new(false);
+    let execution = execute(&mut progs, b"", Limit::Steps(1000), 100, &cancel);
+    assert_eq!(execution.exit_code, ExitCode::Success);
+    assert_eq!(execution.output, b"hello");
+    assert_eq!(execution.max_cell, Some(0));
+    assert_eq!(Language::from_name("ws"), Some(Language::Whitespace));
+}
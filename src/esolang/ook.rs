@@ -0,0 +1,97 @@
+//! Ook!, brainfuck for orangutans. Every pair of the words `Ook.`, `Ook?` and `Ook!` is one
+//! brainfuck instruction, so programs are translated to brainfuck and run like it.
+//! Other words are comments.
+
+use super::ParseError;
+use crate::brainfuck::{parse_dialect, Dialect, Instr};
+
+/// Translates an Ook! program to brainfuck, together with the position of every instruction
+fn translate(source: &str) -> Result<(String, Vec<usize>), ParseError> {
+    let chars = source.chars().collect::<Vec<_>>();
+
+    // the punctuation of every `Ook` word and its position
+    let mut words = Vec::new();
+    let mut start = None;
+    for pos in 0..=chars.len() {
+        let end = chars.get(pos).is_none_or(|ch| ch.is_whitespace());
+        match start {
+            Some(first) if end => {
+                if let ['O', 'o', 'k', mark] = chars[first..pos] {
+                    words.push((mark, first));
+                }
+                start = None;
+            }
+            None if !end => start = Some(pos),
+            _ => {}
+        }
+    }
+
+    let mut brainfuck = String::new();
+    let mut positions = Vec::new();
+    for pair in words.chunks(2) {
+        let position = pair[0].1;
+        let instruction = match pair {
+            [('.', _), ('?', _)] => '>',
+            [('?', _), ('.', _)] => '<',
+            [('.', _), ('.', _)] => '+',
+            [('!', _), ('!', _)] => '-',
+            [('!', _), ('.', _)] => '.',
+            [('.', _), ('!', _)] => ',',
+            [('!', _), ('?', _)] => '[',
+            [('?', _), ('!', _)] => ']',
+            [_] => {
+                return Err(ParseError {
+                    message: "the last `Ook` has no partner",
+                    position,
+                })
+            }
+            _ => {
+                return Err(ParseError {
+                    message: "unknown pair of `Ook`s",
+                    position,
+                })
+            }
+        };
+        brainfuck.push(instruction);
+        positions.push(position);
+    }
+    Ok((brainfuck, positions))
+}
+
+/// Compiles an Ook! program to brainfuck instructions for a dialect
+pub fn parse(source: &str, dialect: Dialect) -> Result<Vec<Instr>, ParseError> {
+    let (brainfuck, positions) = translate(source)?;
+    parse_dialect(&brainfuck, dialect).map_err(|error| ParseError {
+        position: positions.get(error.position).copied().unwrap_or(0),
+        ..error
+    })
+}
+
+#[test]
+fn ook_test() {
+    assert_eq!(
+        parse(
+            "Ook. Ook. comment Ook! Ook.\nOok. Ook? Ook! Ook!",
+            Dialect::default()
+        )
+        .unwrap(),
+        crate::brainfuck::parse_instructions("+.>-").unwrap()
+    );
+    assert_eq!(
+        parse("Ook. Ook. Ook.", Dialect::default())
+            .unwrap_err()
+            .position,
+        10
+    );
+    assert_eq!(
+        parse("Ook? Ook?", Dialect::default()).unwrap_err().position,
+        0
+    );
+    // the unmatched `]` is reported where its pair starts
+    assert_eq!(
+        parse("Ook. Ook. Ook? Ook!", Dialect::default())
+            .unwrap_err()
+            .position,
+        10
+    );
+}
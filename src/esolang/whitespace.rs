@@ -0,0 +1,480 @@
+//! Whitespace, a stack based language that only uses spaces, tabs and line breaks.
+//! All other characters are comments. Besides the stack there is a heap, which maps numbers
+//! to numbers, and subroutines that are called by their labels.
+
+use super::*;
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Token {
+    Space,
+    Tab,
+    Lf,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Instr {
+    Push(i64),
+    Duplicate,
+    /// copies the value some values below the top
+    Copy(i64),
+    Swap,
+    Discard,
+    /// removes some values below the top
+    Slide(i64),
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Store,
+    Retrieve,
+    /// label definitions aren't instructions, they only exist while the program is parsed
+    Mark,
+    Call(usize),
+    Jump(usize),
+    JumpZero(usize),
+    JumpNegative(usize),
+    Return,
+    End,
+    OutputChar,
+    OutputNumber,
+    ReadChar,
+    ReadNumber,
+}
+
+/// Reads the tokens of a program
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    idx: usize,
+    /// position of the instruction that is parsed
+    start: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &'static str) -> ParseError {
+        ParseError {
+            message,
+            position: self.start,
+        }
+    }
+
+    fn next(&mut self) -> Result<Token, ParseError> {
+        let (token, _) = *self
+            .tokens
+            .get(self.idx)
+            .ok_or_else(|| self.error("unfinished instruction"))?;
+        self.idx += 1;
+        Ok(token)
+    }
+
+    fn pair(&mut self) -> Result<(Token, Token), ParseError> {
+        Ok((self.next()?, self.next()?))
+    }
+
+    /// a sign and binary digits, ended by a line break
+    fn number(&mut self) -> Result<i64, ParseError> {
+        let negative = match self.next()? {
+            Token::Lf => return Ok(0),
+            sign => sign == Token::Tab,
+        };
+        let mut number = 0i64;
+        loop {
+            let bit = match self.next()? {
+                Token::Lf => break,
+                bit => (bit == Token::Tab) as i64,
+            };
+            number = number
+                .checked_mul(2)
+                .ok_or_else(|| self.error("the number is too big"))?
+                | bit;
+        }
+        Ok(if negative { -number } else { number })
+    }
+
+    /// spaces and tabs, ended by a line break
+    fn label(&mut self) -> Result<Vec<Token>, ParseError> {
+        let mut label = Vec::new();
+        loop {
+            match self.next()? {
+                Token::Lf => return Ok(label),
+                token => label.push(token),
+            }
+        }
+    }
+
+    /// the next instruction and the label it uses or defines
+    fn instruction(&mut self) -> Result<(Instr, Option<Vec<Token>>), ParseError> {
+        use Token::*;
+        self.start = self.tokens[self.idx].1;
+        let instr = match self.next()? {
+            Space => match self.next()? {
+                Space => Instr::Push(self.number()?),
+                Tab => match self.next()? {
+                    Space => Instr::Copy(self.number()?),
+                    Lf => Instr::Slide(self.number()?),
+                    Tab => return Err(self.error("unknown stack instruction")),
+                },
+                Lf => match self.next()? {
+                    Space => Instr::Duplicate,
+                    Tab => Instr::Swap,
+                    Lf => Instr::Discard,
+                },
+            },
+            Tab => match self.next()? {
+                Space => match self.pair()? {
+                    (Space, Space) => Instr::Add,
+                    (Space, Tab) => Instr::Subtract,
+                    (Space, Lf) => Instr::Multiply,
+                    (Tab, Space) => Instr::Divide,
+                    (Tab, Tab) => Instr::Modulo,
+                    _ => return Err(self.error("unknown arithmetic instruction")),
+                },
+                Tab => match self.next()? {
+                    Space => Instr::Store,
+                    Tab => Instr::Retrieve,
+                    Lf => return Err(self.error("unknown heap instruction")),
+                },
+                Lf => match self.pair()? {
+                    (Space, Space) => Instr::OutputChar,
+                    (Space, Tab) => Instr::OutputNumber,
+                    (Tab, Space) => Instr::ReadChar,
+                    (Tab, Tab) => Instr::ReadNumber,
+                    _ => return Err(self.error("unknown I/O instruction")),
+                },
+            },
+            Lf => {
+                let instr = match self.pair()? {
+                    (Space, Space) => Instr::Mark,
+                    (Space, Tab) => Instr::Call(0),
+                    (Space, Lf) => Instr::Jump(0),
+                    (Tab, Space) => Instr::JumpZero(0),
+                    (Tab, Tab) => Instr::JumpNegative(0),
+                    (Tab, Lf) => return Ok((Instr::Return, None)),
+                    (Lf, Lf) => return Ok((Instr::End, None)),
+                    _ => return Err(self.error("unknown flow control instruction")),
+                };
+                return Ok((instr, Some(self.label()?)));
+            }
+        };
+        Ok((instr, None))
+    }
+}
+
+/// Compiles a program, jumps go to the index of the instruction after their label
+fn parse(source: &str) -> Result<Vec<Instr>, ParseError> {
+    let tokens = source
+        .chars()
+        .enumerate()
+        .filter_map(|(pos, ch)| match ch {
+            ' ' => Some((Token::Space, pos)),
+            '\t' => Some((Token::Tab, pos)),
+            '\n' => Some((Token::Lf, pos)),
+            _ => None,
+        })
+        .collect();
+    let mut parser = Parser {
+        tokens,
+        idx: 0,
+        start: 0,
+    };
+
+    let mut code = Vec::new();
+    let mut marks = HashMap::new();
+    // instructions that jump to a label, with the label and their position
+    let mut jumps = Vec::new();
+    while parser.idx < parser.tokens.len() {
+        match parser.instruction()? {
+            (Instr::Mark, Some(label)) => {
+                if marks.insert(label, code.len()).is_some() {
+                    return Err(parser.error("the label is defined twice"));
+                }
+            }
+            (instr, Some(label)) => {
+                jumps.push((code.len(), label, parser.start));
+                code.push(instr);
+            }
+            (instr, None) => code.push(instr),
+        }
+    }
+
+    for (idx, label, position) in jumps {
+        let target = *marks.get(&label).ok_or(ParseError {
+            message: "the label isn't defined",
+            position,
+        })?;
+        code[idx] = match code[idx] {
+            Instr::Call(_) => Instr::Call(target),
+            Instr::Jump(_) => Instr::Jump(target),
+            Instr::JumpZero(_) => Instr::JumpZero(target),
+            Instr::JumpNegative(_) => Instr::JumpNegative(target),
+            instr => instr,
+        };
+    }
+    Ok(code)
+}
+
+/// division that rounds down, like in the original interpreter
+fn divide(a: i64, b: i64) -> i64 {
+    let quotient = a.wrapping_div(b);
+    if a.wrapping_rem(b) != 0 && (a < 0) != (b < 0) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+pub struct Whitespace {
+    code: Vec<Instr>,
+    ip: usize,
+    stack: Vec<i64>,
+    heap: HashMap<i64, i64>,
+    /// where the called subroutines return to
+    calls: Vec<usize>,
+    /// bytes of a number that still have to be written
+    pending: VecDeque<u8>,
+    /// the line that is read as a number
+    line: Vec<u8>,
+    /// the highest heap address that was used
+    max_cell: usize,
+}
+
+impl Whitespace {
+    pub fn new(source: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            code: parse(source)?,
+            ip: 0,
+            stack: Vec::new(),
+            heap: HashMap::new(),
+            calls: Vec::new(),
+            pending: VecDeque::new(),
+            line: Vec::new(),
+            max_cell: 0,
+        })
+    }
+
+    fn pop(&mut self) -> Result<i64, ExitCode> {
+        self.stack.pop().ok_or(ExitCode::StackUnderflow)
+    }
+
+    fn push(&mut self, value: i64) -> Result<(), ExitCode> {
+        if self.stack.len() >= MAX_MEMORY {
+            return Err(ExitCode::OutOfMemory);
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    /// replaces the two top values by the result of an operation on them
+    fn binary(
+        &mut self,
+        operation: impl FnOnce(i64, i64) -> Result<i64, ExitCode>,
+    ) -> Result<(), ExitCode> {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        self.push(operation(a, b)?)
+    }
+
+    /// stores a value at the heap address below it on the stack
+    fn store(&mut self, value: i64) -> Result<(), ExitCode> {
+        let address = self.pop()?;
+        if self.heap.len() >= MAX_MEMORY && !self.heap.contains_key(&address) {
+            return Err(ExitCode::OutOfMemory);
+        }
+        self.heap.insert(address, value);
+        if let Ok(address) = usize::try_from(address) {
+            self.max_cell = self.max_cell.max(address);
+        }
+        Ok(())
+    }
+
+    fn jump(&mut self, target: usize) -> Result<Option<Output>, ExitCode> {
+        self.ip = target;
+        Ok(None)
+    }
+
+    fn execute(&mut self, input: &mut Option<Input>) -> Result<Option<Output>, ExitCode> {
+        // the program also ends when it runs out of instructions
+        let instr = match self.code.get(self.ip) {
+            Some(&instr) => instr,
+            None => return Ok(Some(Output::Terminated)),
+        };
+        match instr {
+            Instr::Push(value) => self.push(value)?,
+            Instr::Duplicate => {
+                let value = *self.stack.last().ok_or(ExitCode::StackUnderflow)?;
+                self.push(value)?;
+            }
+            Instr::Copy(depth) => {
+                let value = usize::try_from(depth)
+                    .ok()
+                    .and_then(|depth| self.stack.len().checked_sub(depth + 1))
+                    .map(|idx| self.stack[idx])
+                    .ok_or(ExitCode::StackUnderflow)?;
+                self.push(value)?;
+            }
+            Instr::Swap => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.push(b)?;
+                self.push(a)?;
+            }
+            Instr::Discard => {
+                self.pop()?;
+            }
+            Instr::Slide(count) => {
+                let top = self.pop()?;
+                let count = usize::try_from(count).unwrap_or(0);
+                self.stack.truncate(self.stack.len().saturating_sub(count));
+                self.push(top)?;
+            }
+            Instr::Add => self.binary(|a, b| Ok(a.wrapping_add(b)))?,
+            Instr::Subtract => self.binary(|a, b| Ok(a.wrapping_sub(b)))?,
+            Instr::Multiply => self.binary(|a, b| Ok(a.wrapping_mul(b)))?,
+            Instr::Divide => self.binary(|a, b| match b {
+                0 => Err(ExitCode::DivisionByZero),
+                _ => Ok(divide(a, b)),
+            })?,
+            Instr::Modulo => self.binary(|a, b| match b {
+                0 => Err(ExitCode::DivisionByZero),
+                _ => Ok(a.wrapping_sub(b.wrapping_mul(divide(a, b)))),
+            })?,
+            Instr::Store => {
+                let value = self.pop()?;
+                self.store(value)?;
+            }
+            Instr::Retrieve => {
+                let address = self.pop()?;
+                let value = self.heap.get(&address).copied().unwrap_or(0);
+                self.push(value)?;
+            }
+            Instr::Mark => {}
+            Instr::Call(target) => {
+                if self.calls.len() >= MAX_MEMORY {
+                    return Err(ExitCode::OutOfMemory);
+                }
+                self.calls.push(self.ip + 1);
+                return self.jump(target);
+            }
+            Instr::Jump(target) => return self.jump(target),
+            Instr::JumpZero(target) => {
+                if self.pop()? == 0 {
+                    return self.jump(target);
+                }
+            }
+            Instr::JumpNegative(target) => {
+                if self.pop()? < 0 {
+                    return self.jump(target);
+                }
+            }
+            Instr::Return => {
+                let target = self.calls.pop().ok_or(ExitCode::StackUnderflow)?;
+                return self.jump(target);
+            }
+            Instr::End => return Ok(Some(Output::Terminated)),
+            Instr::OutputChar => {
+                let value = self.pop()?;
+                self.ip += 1;
+                return Ok(Some(Output::Value(value as u8)));
+            }
+            Instr::OutputNumber => {
+                let value = self.pop()?;
+                self.pending.extend(value.to_string().bytes());
+                self.ip += 1;
+                return Ok(self.pending.pop_front().map(Output::Value));
+            }
+            Instr::ReadChar => {
+                let value = match input.take() {
+                    None => return Ok(Some(Output::Starved)),
+                    Some(Input::Byte(byte)) => byte.into(),
+                    Some(Input::Eof) => -1,
+                };
+                self.store(value)?;
+            }
+            Instr::ReadNumber => match input.take() {
+                None => return Ok(Some(Output::Starved)),
+                Some(Input::Byte(b'\n')) | Some(Input::Eof) => {
+                    let line = std::mem::take(&mut self.line);
+                    let line = String::from_utf8_lossy(&line);
+                    let value = match line.trim() {
+                        "" => -1,
+                        number => number.parse().unwrap_or(0),
+                    };
+                    self.store(value)?;
+                }
+                Some(Input::Byte(byte)) => {
+                    // nobody writes numbers that long
+                    if self.line.len() < 64 {
+                        self.line.push(byte);
+                    }
+                    return Ok(None);
+                }
+            },
+        }
+        self.ip += 1;
+        Ok(None)
+    }
+}
+
+impl Interpreter for Whitespace {
+    fn step(&mut self, input: &mut Option<Input>) -> Option<Output> {
+        if let Some(byte) = self.pending.pop_front() {
+            return Some(Output::Value(byte));
+        }
+        match self.execute(input) {
+            Ok(output) => output,
+            Err(exit_code) => Some(Output::Error(exit_code)),
+        }
+    }
+
+    fn max_cell(&self) -> Option<usize> {
+        Some(self.max_cell)
+    }
+}
+
+/// A program written with `S`, `T` and `L` instead of spaces, tabs and line breaks
+#[cfg(test)]
+fn run(program: &str, input: &str) -> (Vec<u8>, ExitCode) {
+    let program = program
+        .chars()
+        .filter_map(|ch| match ch {
+            'S' => Some(' '),
+            'T' => Some('\t'),
+            'L' => Some('\n'),
+            _ => None,
+        })
+        .collect::<String>();
+    let mut progs = [Whitespace::new(&program).unwrap()];
+    let cancel = AtomicBool::new(false);
+    let execution = execute(
+        &mut progs,
+        input.as_bytes(),
+        Limit::Steps(10_000),
+        100,
+        &cancel,
+    );
+    (execution.output, execution.exit_code)
+}
+
+#[test]
+fn whitespace_test() {
+    // push 'H', write it, push 'i', write it, end
+    let hello = run("SSSTSSTSSSL TLSS SSSTTSTSSTL TLSS LLL", "");
+    assert_eq!(hello, (b"Hi".to_vec(), ExitCode::Success));
+    // read a number to the heap, add one and write it
+    let add = run("SSSL TLTT SSSL TTT SSSTL TSSS TLST LLL", "41\n");
+    assert_eq!(add.0, b"42");
+    // call a subroutine that writes the top of the stack
+    let call = run("SSSTL LSTSL LLL LSSSL TLST LTL", "");
+    assert_eq!(call, (b"1".to_vec(), ExitCode::Success));
+    assert_eq!(run("TLSS", "").1, ExitCode::StackUnderflow);
+    assert_eq!(run("SSSTL SSSL TSTS LLL", "").1, ExitCode::DivisionByZero);
+    assert_eq!(divide(-7, 2), -4);
+
+    assert_eq!(parse("\n \n\t\n").unwrap_err().position, 0);
+    assert_eq!(
+        parse("  \t\n\t").unwrap_err().message,
+        "unfinished instruction"
+    );
+}
@@ -0,0 +1,129 @@
+//! Reviewing finished games and the openings of many games
+
+use super::*;
+use std::collections::HashMap;
+
+/// How much the chance to win has to drop for a move to be a blunder
+pub const BLUNDER: f64 = 0.3;
+
+/// The evaluation of a single move
+#[derive(Clone, Debug, PartialEq)]
+pub struct Review {
+    /// the player that made the move
+    pub player: usize,
+    /// chance to win of the player before the move
+    pub before: f64,
+    /// chance to win of the player after the move
+    pub after: f64,
+}
+
+impl Review {
+    pub fn is_blunder(&self) -> bool {
+        self.before - self.after >= BLUNDER
+    }
+}
+
+/// Replays a game and evaluates every move, `None` if the AI can't evaluate the positions
+/// or a move is invalid
+pub fn analyze<T: Clone, G: PvpGame<T> + Clone>(
+    start: &G,
+    moves: &[T],
+    ai: &mut dyn AiPlayer<T, G>,
+) -> Option<Vec<Review>> {
+    let mut game = start.clone();
+    let mut reviews = Vec::new();
+    // every position is only evaluated once, from the view of the player to move
+    let mut before = ai.evaluate(&game, 0)?;
+    for (idx, play) in moves.iter().enumerate() {
+        let player = idx % 2;
+        let after = match game.make_move(play.clone(), player) {
+            GameState::Invalid => return None,
+            GameState::Win(winner) if winner == player => 1.0,
+            GameState::Win(_) => 0.0,
+            GameState::Tie => 0.5,
+            GameState::Running => 1.0 - ai.evaluate(&game, 1 - player)?,
+        };
+        reviews.push(Review {
+            player,
+            before,
+            after,
+        });
+        before = 1.0 - after;
+    }
+    Some(reviews)
+}
+
+/// The results of all games that started with the same move
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Opening {
+    /// the encoded first move
+    pub first_move: Vec<u8>,
+    pub games: usize,
+    /// wins of the player that made the first move
+    pub wins: usize,
+    pub draws: usize,
+}
+
+impl Opening {
+    /// points per game of the player that made the first move
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games as f64
+    }
+}
+
+/// Groups games by their encoded first move, the most common first.
+/// Each game consists of the encoded moves and the winner.
+pub fn openings(games: &[(Vec<u8>, Option<usize>)], move_size: usize) -> Vec<Opening> {
+    let mut openings = HashMap::<&[u8], Opening>::new();
+    for (moves, winner) in games {
+        let first_move = match moves.get(..move_size) {
+            Some(first_move) => first_move,
+            None => continue,
+        };
+        let opening = openings.entry(first_move).or_default();
+        opening.games += 1;
+        match winner {
+            Some(0) => opening.wins += 1,
+            None => opening.draws += 1,
+            _ => {}
+        }
+    }
+
+    let mut openings = openings
+        .into_iter()
+        .map(|(first_move, opening)| Opening {
+            first_move: first_move.to_vec(),
+            ..opening
+        })
+        .collect::<Vec<_>>();
+    openings.sort_by(|a, b| b.games.cmp(&a.games).then(a.first_move.cmp(&b.first_move)));
+    openings
+}
+
+#[test]
+fn analyze_test() {
+    let mut ai = Minimax(tictactoe::TTTAI);
+    let start = tictactoe::TTTField::default();
+    // the second player misses that the first one gets two open rows
+    let reviews = analyze(&start, &[4, 1, 0, 8, 6, 2, 3], &mut ai).unwrap();
+    assert_eq!(reviews.len(), 7);
+    assert_eq!(reviews[6].after, 1.0);
+    assert!(reviews[1].is_blunder());
+    assert!(!reviews[0].is_blunder());
+}
+
+#[test]
+fn openings_test() {
+    let games = vec![
+        (vec![3, 4], Some(0)),
+        (vec![3, 2], None),
+        (vec![1, 4], Some(1)),
+        (vec![], None),
+    ];
+    let openings = openings(&games, 1);
+    assert_eq!(openings.len(), 2);
+    assert_eq!(openings[0].first_move, vec![3]);
+    assert_eq!(openings[0].games, 2);
+    assert_eq!(openings[0].score(), 0.75);
+    assert_eq!(openings[1].score(), 0.0);
+}
@@ -0,0 +1,191 @@
+//! Time controls of a game
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+
+/// How much time the players have for their moves
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimeControl {
+    /// Every move has to be made within the time
+    PerMove(Duration),
+    /// Every player has a total time for the game, which grows by the increment after each of
+    /// their moves
+    Fischer {
+        total: Duration,
+        increment: Duration,
+    },
+    /// Like `PerMove`, but with hours instead of seconds, for games that last days
+    Correspondence(Duration),
+}
+
+impl TimeControl {
+    /// Describes the time control in words
+    pub fn describe(&self) -> String {
+        match self {
+            Self::PerMove(time) => format!("{} seconds per move", time.as_secs()),
+            Self::Fischer { total, increment } => format!(
+                "{} minutes with an increment of {} seconds",
+                total.as_secs() / MINUTE,
+                increment.as_secs()
+            ),
+            Self::Correspondence(time) => format!("{} hours per move", time.as_secs() / HOUR),
+        }
+    }
+}
+
+/// Writes the time control the way it is parsed, e.g. `60`, `5+3` or `24h`
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::PerMove(time) => write!(f, "{}", time.as_secs()),
+            Self::Fischer { total, increment } => {
+                write!(f, "{}+{}", total.as_secs() / MINUTE, increment.as_secs())
+            }
+            Self::Correspondence(time) => write!(f, "{}h", time.as_secs() / HOUR),
+        }
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = &'static str;
+
+    /// Seconds per move (`60`), minutes and increment in seconds (`5+3`) or hours per move (`24h`)
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim().to_lowercase();
+        let number = |text: &str, range: std::ops::RangeInclusive<u64>, err| match text.parse() {
+            Ok(n) if range.contains(&n) => Ok(n),
+            _ => Err(err),
+        };
+
+        if let Some(hours) = text.strip_suffix('h') {
+            let hours = number(
+                hours,
+                1..=168,
+                "correspondence games have 1 to 168 hours per move",
+            )?;
+            Ok(Self::Correspondence(Duration::from_secs(hours * HOUR)))
+        } else if let Some((total, increment)) = text.split_once('+') {
+            let total = number(total, 1..=180, "the total time has to be 1 to 180 minutes")?;
+            let increment = number(increment, 0..=60, "the increment has to be 0 to 60 seconds")?;
+            Ok(Self::Fischer {
+                total: Duration::from_secs(total * MINUTE),
+                increment: Duration::from_secs(increment),
+            })
+        } else {
+            let text = text.strip_suffix('s').unwrap_or(&text);
+            let secs = number(text, 10..=3600, "a move can take 10 to 3600 seconds")?;
+            Ok(Self::PerMove(Duration::from_secs(secs)))
+        }
+    }
+}
+
+/// The remaining time of both players.
+///
+/// The clock doesn't measure time itself, the front end passes the time the current move took.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        let time = match control {
+            TimeControl::PerMove(time) | TimeControl::Correspondence(time) => time,
+            TimeControl::Fischer { total, .. } => total,
+        };
+        Self {
+            control,
+            remaining: [time; 2],
+        }
+    }
+
+    /// A clock that already ran, with the remaining time of both players
+    pub fn resume(control: TimeControl, remaining: [Duration; 2]) -> Self {
+        Self { control, remaining }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// Remaining time of both players at the start of their next move
+    pub fn remaining(&self) -> [Duration; 2] {
+        self.remaining
+    }
+
+    /// Time the player has left, when their current move took `elapsed` so far
+    pub fn time_left(&self, player: usize, elapsed: Duration) -> Duration {
+        self.remaining[player].saturating_sub(elapsed)
+    }
+
+    /// Whether the player ran out of time
+    pub fn is_flagged(&self, player: usize, elapsed: Duration) -> bool {
+        self.time_left(player, elapsed) == Duration::ZERO
+    }
+
+    /// Stops the clock of the player after their move, which took `elapsed`
+    pub fn finish_move(&mut self, player: usize, elapsed: Duration) {
+        if let TimeControl::Fischer { increment, .. } = self.control {
+            self.remaining[player] = self.time_left(player, elapsed) + increment;
+        }
+    }
+}
+
+/// Formats a duration like a clock, e.g. `4:05`, `1:02:03` or `2d 03h` for long durations
+pub fn format_duration(time: Duration) -> String {
+    let secs = time.as_secs();
+    let (days, hours, mins, secs) = (
+        secs / 24 / HOUR,
+        secs / HOUR % 24,
+        secs / MINUTE % 60,
+        secs % 60,
+    );
+    if days > 0 {
+        format!("{}d {:02}h", days, hours)
+    } else if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, mins, secs)
+    } else {
+        format!("{}:{:02}", mins, secs)
+    }
+}
+
+#[test]
+fn time_control_parse_test() {
+    let secs = Duration::from_secs;
+    assert_eq!("60".parse(), Ok(TimeControl::PerMove(secs(60))));
+    assert_eq!("45s".parse(), Ok(TimeControl::PerMove(secs(45))));
+    assert_eq!(
+        "5+3".parse(),
+        Ok(TimeControl::Fischer {
+            total: secs(300),
+            increment: secs(3)
+        })
+    );
+    assert_eq!("24h".parse(), Ok(TimeControl::Correspondence(secs(86400))));
+    assert!("0".parse::<TimeControl>().is_err());
+    assert!("0h".parse::<TimeControl>().is_err());
+    assert!("5+".parse::<TimeControl>().is_err());
+
+    for text in ["60", "5+3", "24h"].iter() {
+        assert_eq!(&text.parse::<TimeControl>().unwrap().to_string(), text);
+    }
+}
+
+#[test]
+fn fischer_clock_test() {
+    let secs = Duration::from_secs;
+    let mut clock = Clock::new("1+5".parse().unwrap());
+
+    clock.finish_move(0, secs(20));
+    assert_eq!(clock.time_left(0, secs(0)), secs(45));
+    assert_eq!(clock.time_left(1, secs(10)), secs(50));
+
+    // the increment doesn't save a player whose time ran out
+    assert!(clock.is_flagged(1, secs(60)));
+    assert!(!clock.is_flagged(0, secs(44)));
+}
@@ -0,0 +1,102 @@
+use super::*;
+use cartesian::*;
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Connect4 {
+    field: [[Option<usize>; ROWS]; COLS],
+}
+
+pub const ROWS: usize = 6;
+pub const COLS: usize = 7;
+
+/// Seconds the analysis spends on each position
+const ANALYSIS_TIME: f64 = 0.3;
+
+impl Connect4 {
+    fn filled(&self) -> bool {
+        self.field.iter().all(|col| col.iter().all(Option::is_some))
+    }
+}
+
+/// position of a cell in the drawn grid, where the lowest row is at the bottom
+fn grid_pos((x, y): (usize, usize)) -> (usize, usize) {
+    (x, ROWS - 1 - y)
+}
+
+impl PvpGame<usize> for Connect4 {
+    fn board(&self) -> Board {
+        let mut grid = Vec::new();
+        for y in (0..ROWS).rev() {
+            let row = (0..COLS)
+                .map(|x| match self.field[x][y] {
+                    Some(player) => Tile::Piece(player),
+                    None => Tile::Empty,
+                })
+                .collect();
+            grid.push(row);
+        }
+        grid.push((0..COLS).map(Tile::Label).collect());
+
+        let mut board = Board::new(grid, Color::White);
+        if let Some(row) = util::find_row(ROWS, COLS, &|x, y| self.field[x][y], 4) {
+            board.line = vec![grid_pos(row[0]), grid_pos(row[3])];
+        }
+        board
+    }
+    fn move_tiles(&self, &idx: &usize) -> Vec<(usize, usize)> {
+        let height = self.field[idx].iter().filter(|e| e.is_some()).count();
+        vec![grid_pos((idx, height.max(1) - 1))]
+    }
+    fn describe_move(&self, &idx: &usize) -> String {
+        let height = self.field[idx].iter().filter(|e| e.is_some()).count();
+        format!(
+            "dropped a disc into column {}, landing in row {} from the bottom",
+            idx,
+            height + 1
+        )
+    }
+    fn parse_move(text: &str) -> Option<usize> {
+        parse_label(text, 0..COLS)
+    }
+    fn make_move(&mut self, idx: usize, person: usize) -> GameState {
+        assert!(idx < COLS);
+        for entry in self.field[idx].iter_mut() {
+            if entry.is_none() {
+                *entry = Some(person);
+                return self.status();
+            }
+        }
+        // this column is already filled to the top
+        return GameState::Invalid;
+    }
+    fn status(&self) -> GameState {
+        util::n_in_a_row(ROWS, COLS, &|x, y| self.field[x][y], 4)
+    }
+    fn title() -> &'static str {
+        "Connect Four"
+    }
+    fn pieces() -> [Piece; 2] {
+        tictactoe::TTTField::pieces()
+    }
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+    fn possible_moves(&self, _: usize) -> Vec<usize> {
+        (0..COLS)
+            .filter(|&x| self.field[x][ROWS - 1].is_none())
+            .collect()
+    }
+    fn analysis_ai() -> Option<Box<dyn AiPlayer<usize, Self> + Send + Sync>> {
+        Some(Box::new(mcts::TreeSearchAi::new(ANALYSIS_TIME)))
+    }
+}
+
+#[test]
+fn connect4_column_win_test() {
+    let mut game = Connect4::default();
+    for _ in 0..3 {
+        assert_eq!(game.make_move(0, 0), GameState::Running);
+        assert_eq!(game.make_move(1, 1), GameState::Running);
+    }
+    assert_eq!(game.make_move(0, 0), GameState::Win(0));
+}
@@ -0,0 +1,49 @@
+//! Elo rating math
+
+pub type Elo = f64;
+
+/// Rating of a player that hasn't played yet
+pub const DEFAULT_ELO: Elo = 1200.0;
+
+const K: f64 = 40.0;
+
+/// expected score for player 0
+pub fn expected_score(elo: [Elo; 2]) -> f64 {
+    1.0 / (1.0 + 10.0_f64.powf((elo[1] - elo[0]) / 400.0))
+}
+
+/// new ratings of both players after a game
+pub fn rate_game(elo: [Elo; 2], winner: Option<usize>) -> [Elo; 2] {
+    // actual score for player 0
+    let score0 = winner.map_or(0.5, |p| 1.0 - p as f64);
+
+    // calculate elo addition/subtraction
+    let d_elo = K * (score0 - expected_score(elo));
+
+    [elo[0] + d_elo, elo[1] - d_elo]
+}
+
+/// A rating history drawn with block characters, one for each rating
+pub fn sparkline(ratings: &[Elo]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let min = ratings.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = ratings.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    ratings
+        .iter()
+        .map(|&elo| {
+            let level = if max > min {
+                ((elo - min) / (max - min) * 7.0).round() as usize
+            } else {
+                0
+            };
+            BLOCKS[level]
+        })
+        .collect()
+}
+
+#[test]
+fn sparkline_test() {
+    assert_eq!(sparkline(&[]), "");
+    assert_eq!(sparkline(&[1200.0, 1200.0]), "▁▁");
+    assert_eq!(sparkline(&[1200.0, 1270.0, 1235.0]), "▁█▅");
+}
@@ -0,0 +1,222 @@
+//! Drawing boards as PNG images, for boards that are too big for emojis
+
+use super::render::*;
+use super::util::*;
+
+/// Size of a tile in pixels
+const TILE: usize = 32;
+
+/// Space between two grids of a board
+const GAP: usize = TILE / 2;
+
+const BACKGROUND: [u8; 3] = [32, 34, 37];
+/// Outline of the last move
+const HIGHLIGHT: [u8; 3] = [120, 177, 89];
+/// Winning line
+const LINE: [u8; 3] = [255, 255, 255];
+
+/// 3x5 pixel font for the labels, every row is 3 bits
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width * height],
+        }
+    }
+
+    fn set(&mut self, x: isize, y: isize, color: [u8; 3]) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.pixels[y as usize * self.width + x as usize] = color;
+        }
+    }
+
+    fn rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: [u8; 3]) {
+        for dy in 0..h {
+            for dx in 0..w {
+                self.set((x + dx) as _, (y + dy) as _, color);
+            }
+        }
+    }
+
+    fn circle(&mut self, cx: f64, cy: f64, r: f64, color: [u8; 3]) {
+        let (x0, x1) = ((cx - r).floor() as isize, (cx + r).ceil() as isize);
+        let (y0, y1) = ((cy - r).floor() as isize, (cy + r).ceil() as isize);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let (dx, dy) = (x as f64 + 0.5 - cx, y as f64 + 0.5 - cy);
+                if dx * dx + dy * dy <= r * r {
+                    self.set(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: [u8; 3]) {
+        let len = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+        let steps = len.ceil() as usize + 1;
+        for i in 0..=steps {
+            let t = i as f64 / steps as f64;
+            let x = from.0 + t * (to.0 - from.0);
+            let y = from.1 + t * (to.1 - from.1);
+            self.circle(x, y, width / 2.0, color);
+        }
+    }
+
+    fn digit(&mut self, x: usize, y: usize, digit: usize, color: [u8; 3]) {
+        let scale = TILE / 8;
+        let (x, y) = (x + (TILE - 3 * scale) / 2, y + (TILE - 5 * scale) / 2);
+        for (row, bits) in DIGITS[digit % 10].iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) != 0 {
+                    self.rect(x + col * scale, y + row * scale, scale, scale, color);
+                }
+            }
+        }
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut data, self.width as u32, self.height as u32);
+            encoder.set_color(png::ColorType::RGB);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels.concat())?;
+        }
+        Ok(data)
+    }
+}
+
+fn draw_tile(
+    canvas: &mut Canvas,
+    x: usize,
+    y: usize,
+    tile: Tile,
+    board: &Board,
+    pieces: &[Piece; 2],
+) {
+    let black = Color::Black.rgb();
+    let center = ((x + TILE / 2) as f64, (y + TILE / 2) as f64);
+    let margin = TILE / 10;
+
+    match tile {
+        Tile::Border => canvas.rect(x, y, TILE, TILE, board.border.rgb()),
+        _ => canvas.rect(x, y, TILE, TILE, black),
+    }
+
+    match tile {
+        Tile::Empty => canvas.circle(center.0, center.1, (TILE / 8) as f64, BACKGROUND),
+        Tile::Neutral => {
+            let size = TILE - 2 * margin;
+            canvas.rect(x + margin, y + margin, size, size, Color::Blue.rgb());
+        }
+        Tile::Piece(p) => match pieces[p] {
+            Piece(Shape::Square, color) => {
+                let size = TILE - 2 * margin;
+                canvas.rect(x + margin, y + margin, size, size, color.rgb());
+            }
+            Piece(Shape::Circle, color) => {
+                let r = (TILE / 2 - margin) as f64;
+                canvas.circle(center.0, center.1, r, color.rgb());
+            }
+        },
+        Tile::Label(num) => canvas.digit(x, y, num, Color::White.rgb()),
+        Tile::Border | Tile::Space => {}
+    }
+}
+
+/// Draws the outline around the highlighted tiles
+fn draw_highlight(canvas: &mut Canvas, top: usize, tiles: &[(usize, usize)]) {
+    let width = 3;
+    for &(tx, ty) in tiles.iter() {
+        let (x, y) = (tx * TILE, top + ty * TILE);
+        let has = |dx: isize, dy: isize| {
+            let (nx, ny) = (tx as isize + dx, ty as isize + dy);
+            tiles
+                .iter()
+                .any(|&(hx, hy)| hx as isize == nx && hy as isize == ny)
+        };
+        if !has(0, -1) {
+            canvas.rect(x, y, TILE, width, HIGHLIGHT);
+        }
+        if !has(0, 1) {
+            canvas.rect(x, y + TILE - width, TILE, width, HIGHLIGHT);
+        }
+        if !has(-1, 0) {
+            canvas.rect(x, y, width, TILE, HIGHLIGHT);
+        }
+        if !has(1, 0) {
+            canvas.rect(x + TILE - width, y, width, TILE, HIGHLIGHT);
+        }
+    }
+}
+
+/// Draws all grids of the board below each other, text is left out.
+pub fn render(board: &Board, pieces: &[Piece; 2]) -> Result<Vec<u8>, png::EncodingError> {
+    let grids = board
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Grid(grid) => Some(grid),
+            Part::Text(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    let width = grids
+        .iter()
+        .flat_map(|grid| grid.iter().map(Vec::len))
+        .max()
+        .unwrap_or(0);
+    let height = grids.iter().map(|grid| grid.len()).sum::<usize>();
+    let gaps = grids.len().max(1) - 1;
+
+    let mut canvas = Canvas::new(width * TILE, height * TILE + gaps * GAP);
+
+    let mut top = 0;
+    for (idx, grid) in grids.into_iter().enumerate() {
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                draw_tile(&mut canvas, x * TILE, top + y * TILE, tile, board, pieces);
+            }
+        }
+
+        // highlights belong to the first grid
+        if idx == 0 {
+            draw_highlight(&mut canvas, top, &board.highlight);
+
+            if let (Some(&first), Some(&last)) = (board.line.first(), board.line.last()) {
+                let center = |(x, y): (usize, usize)| {
+                    (
+                        (x * TILE + TILE / 2) as f64,
+                        (top + y * TILE + TILE / 2) as f64,
+                    )
+                };
+                canvas.line(center(first), center(last), (TILE / 5) as f64, LINE);
+            }
+        }
+
+        top += grid.len() * TILE + GAP;
+    }
+
+    canvas.encode()
+}
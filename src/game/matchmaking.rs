@@ -0,0 +1,62 @@
+//! Pairing players that wait for a rated game
+
+use super::elo::Elo;
+use std::time::Duration;
+
+/// Rating difference that is accepted right away
+const INITIAL_WINDOW: f64 = 50.0;
+/// How much the accepted difference grows per second of waiting
+const GROWTH: f64 = 5.0;
+const MAX_WINDOW: f64 = 1000.0;
+
+/// A player waiting for an opponent
+#[derive(Clone, Debug, PartialEq)]
+pub struct Seeker {
+    pub rating: Elo,
+    pub waited: Duration,
+}
+
+/// Rating difference a player accepts after waiting for some time
+pub fn window(waited: Duration) -> f64 {
+    (INITIAL_WINDOW + GROWTH * waited.as_secs_f64()).min(MAX_WINDOW)
+}
+
+/// The two seekers with the closest ratings, if the windows of both of them allow the difference
+pub fn find_match(seekers: &[Seeker]) -> Option<(usize, usize)> {
+    let mut best = None;
+    for (a, first) in seekers.iter().enumerate() {
+        for (b, second) in seekers.iter().enumerate().skip(a + 1) {
+            let diff = (first.rating - second.rating).abs();
+            if diff > window(first.waited).min(window(second.waited)) {
+                continue;
+            }
+            match best {
+                Some((_, _, best_diff)) if best_diff <= diff => {}
+                _ => best = Some((a, b, diff)),
+            }
+        }
+    }
+    best.map(|(a, b, _)| (a, b))
+}
+
+#[test]
+fn find_match_test() {
+    let seeker = |rating, secs| Seeker {
+        rating,
+        waited: Duration::from_secs(secs),
+    };
+
+    // too far apart for players that just started waiting
+    assert_eq!(find_match(&[seeker(1200.0, 0), seeker(1400.0, 0)]), None);
+    // both need to have waited long enough
+    assert_eq!(find_match(&[seeker(1200.0, 60), seeker(1400.0, 0)]), None);
+    assert_eq!(
+        find_match(&[seeker(1200.0, 60), seeker(1400.0, 40)]),
+        Some((0, 1))
+    );
+    // the closest ratings are paired first
+    assert_eq!(
+        find_match(&[seeker(1200.0, 0), seeker(1500.0, 0), seeker(1230.0, 0)]),
+        Some((0, 2))
+    );
+}
@@ -5,6 +5,7 @@ use super::*;
 use rand::prelude::*;
 use std::marker::*;
 use std::ops::*;
+use std::time::*;
 
 pub const VALUE_WEIGHT: f64 = 0.5;
 pub const EXPLORE: f64 = 0.5;
@@ -177,8 +178,9 @@ fn roll_out<G: PvpGame<usize> + Clone>(rng: &mut ThreadRng, game: &G, player: us
     stat
 }
 
-impl<T: PvpGame<usize> + Clone> AiPlayer<usize, T> for TreeSearchAi<T> {
-    fn make_move(&mut self, game: &T, player: usize) -> usize {
+impl<T: PvpGame<usize> + Clone> TreeSearchAi<T> {
+    /// explores the game tree until the time is up
+    fn search(&self, game: &T, player: usize) -> Tree {
         let begin = Instant::now();
         let mut tree = Tree::new(game, player);
 
@@ -189,6 +191,23 @@ impl<T: PvpGame<usize> + Clone> AiPlayer<usize, T> for TreeSearchAi<T> {
             tree.improve::<T>(rollouts, &mut rng, &mut game, player);
             rollouts += ROLLOUT_REPS;
         }
+        tree
+    }
+}
+
+impl<T: PvpGame<usize> + Clone> AiPlayer<usize, T> for TreeSearchAi<T> {
+    fn evaluate(&mut self, game: &T, player: usize) -> Option<f64> {
+        // the statistics of the root are from the view of the player to move
+        let Stat { win, loss, tie } = self.search(game, player).stat;
+        let games = win + loss + tie;
+        if games == 0 {
+            return None;
+        }
+        Some((win as f64 + tie as f64 / 2.0) / games as f64)
+    }
+
+    fn make_move(&mut self, game: &T, player: usize) -> usize {
+        let tree = self.search(game, player);
 
         // choose best move
         let mut moves = tree
@@ -49,4 +49,7 @@ impl<G: PvpGame<usize> + Clone, M: MinimaxAi<G>> AiPlayer<usize, G> for Minimax<
     fn make_move(&mut self, board: &G, id: usize) -> usize {
         minimax(&self.0, board, id, self.0.depth() + 1).1
     }
+    fn evaluate(&mut self, board: &G, id: usize) -> Option<f64> {
+        Some(minimax(&self.0, board, id, self.0.depth() + 1).0)
+    }
 }
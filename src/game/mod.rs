@@ -0,0 +1,141 @@
+//! Discord independent game engine.
+//!
+//! Contains the rules of all games, the AIs, the encoding of moves for the game logs and the
+//! rating math. Front ends (like the discord bot) only need to provide a way to display the
+//! board and to read moves from the players.
+
+pub mod analysis;
+pub mod clock;
+pub mod connect4;
+pub mod elo;
+pub mod image;
+pub mod matchmaking;
+pub mod mcts;
+pub mod minimax;
+pub mod pentago;
+pub mod random_ai;
+pub mod render;
+pub mod tictactoe;
+pub mod tournament;
+pub mod ultimate;
+pub mod util;
+use minimax::*;
+use random_ai::*;
+use render::*;
+use util::*;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GameState {
+    Running,
+    Tie,
+    Invalid,
+    Win(usize),
+}
+
+impl GameState {
+    pub fn is_finished(&self) -> bool {
+        match self {
+            Self::Tie => true,
+            Self::Win(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// All functions a game must possess
+pub trait PvpGame<T> {
+    /// Title of the game
+    fn title() -> &'static str;
+    /// The current board, as it is displayed by the renderers
+    fn board(&self) -> Board;
+    /// Display the current board
+    fn draw(&self, renderer: &dyn Renderer) -> String {
+        renderer.render(&self.board(), &Self::pieces())
+    }
+    /// Describe a move in words, called before the move is made
+    fn describe_move(&self, action: &T) -> String;
+    /// Tiles of the board that show a move, called after the move is made
+    fn move_tiles(&self, _action: &T) -> Vec<(usize, usize)> {
+        Vec::new()
+    }
+    /// The board with the last move highlighted
+    fn highlighted_board(&self, last_move: Option<&T>) -> Board {
+        let mut board = self.board();
+        if let Some(action) = last_move {
+            board.highlight = self.move_tiles(action);
+        }
+        board
+    }
+    /// Make a game move
+    fn make_move(&mut self, action: T, person: usize) -> GameState;
+    /// Parse a move typed by a player, using the labels that `draw` displays
+    fn parse_move(text: &str) -> Option<T>;
+    fn status(&self) -> GameState;
+    fn winner(&self) -> Option<usize> {
+        if let GameState::Win(winner) = self.status() {
+            Some(winner)
+        } else {
+            None
+        }
+    }
+    fn ai() -> Option<Box<dyn AiPlayer<T, Self> + Send + Sync>> {
+        None
+    }
+    /// The AI that rates the positions of finished games, it has to be able to evaluate them
+    fn analysis_ai() -> Option<Box<dyn AiPlayer<T, Self> + Send + Sync>> {
+        Self::ai()
+    }
+    fn possible_moves(&self, _player: usize) -> Vec<T> {
+        Vec::new()
+    }
+    /// How the pieces of both players look like
+    fn pieces() -> [Piece; 2];
+    fn is_empty(&self) -> bool;
+}
+
+pub trait AiPlayer<T, G: PvpGame<T>> {
+    fn make_move(&mut self, game: &G, player_id: usize) -> T;
+    /// Chance of the player to win from this position when it is their turn, if the AI can tell
+    fn evaluate(&mut self, _game: &G, _player_id: usize) -> Option<f64> {
+        None
+    }
+}
+
+/// A move that can be stored in the game logs.
+///
+/// Every move of a game is encoded into the same number of bytes,
+/// so a list of moves can be stored as a single blob.
+pub trait Move: Sized + Clone {
+    /// Number of bytes of an encoded move
+    const SIZE: usize;
+    fn encode(&self) -> Vec<u8>;
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+impl Move for usize {
+    const SIZE: usize = 1;
+    fn encode(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        bytes.first().map(|&b| b as usize)
+    }
+}
+
+pub fn encode_moves<T: Move>(moves: &[T]) -> Vec<u8> {
+    moves.iter().flat_map(Move::encode).collect()
+}
+
+pub fn decode_moves<T: Move>(bytes: &[u8]) -> Option<Vec<T>> {
+    if !bytes.len().is_multiple_of(T::SIZE) {
+        return None;
+    }
+    bytes.chunks(T::SIZE).map(T::decode).collect()
+}
+
+#[test]
+fn move_encoding_test() {
+    let moves: Vec<pentago::PMove> = vec!["314a".parse().unwrap(), "661c".parse().unwrap()];
+    let decoded = decode_moves::<pentago::PMove>(&encode_moves(&moves)).unwrap();
+    assert_eq!(moves, decoded);
+}
@@ -0,0 +1,225 @@
+use super::*;
+use std::str::FromStr;
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Pentago {
+    field: [[Option<usize>; 6]; 6],
+    /// where the marble of the last move ended up
+    last: Option<(usize, usize)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PMove {
+    x: usize,
+    y: usize,
+    sel: usize,
+    dir: usize,
+}
+
+const ANTI_CLOCKWISE: usize = 1;
+const CLOCKWISE: usize = 3;
+
+impl FromStr for PMove {
+    type Err = &'static str;
+
+    /// parses a move in the format `XYSR`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.to_lowercase().chars().collect::<Vec<char>>();
+
+        if text.len() < 4 {
+            Err("text too short.")?;
+        }
+
+        let digit = |i: usize| -> Result<usize, Self::Err> {
+            let digit = text[i].to_digit(10).ok_or("no digit")? as usize;
+            digit.checked_sub(1).ok_or("digits start at 1")
+        };
+
+        let x = digit(0)?;
+        let y = digit(1)?;
+        let s = digit(2)?;
+
+        let dir = if text[3] == 'a' {
+            ANTI_CLOCKWISE
+        } else {
+            CLOCKWISE
+        };
+
+        Ok(PMove { x, y, sel: s, dir })
+    }
+}
+
+impl Move for PMove {
+    const SIZE: usize = 2;
+    fn encode(&self) -> Vec<u8> {
+        vec![(6 * self.x + self.y) as u8, (4 * self.sel + self.dir) as u8]
+    }
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let (pos, rot) = (*bytes.first()? as usize, *bytes.get(1)? as usize);
+        Some(Self {
+            x: pos / 6,
+            y: pos % 6,
+            sel: rot / 4,
+            dir: rot % 4,
+        })
+    }
+}
+
+/// position of a cell in the drawn grid
+fn grid_pos((x, y): (usize, usize)) -> (usize, usize) {
+    (2 * x + 1, 2 * y + 1)
+}
+
+impl PvpGame<PMove> for Pentago {
+    fn title() -> &'static str {
+        "Pentago"
+    }
+    fn board(&self) -> Board {
+        let mut grid = Vec::new();
+
+        let mut hor_border = vec![Tile::Border];
+        for x in 1..=6 {
+            hor_border.push(Tile::Label(x));
+            hor_border.push(Tile::Border);
+        }
+
+        grid.push(hor_border.clone());
+
+        for y in 0..6 {
+            let mut row = vec![Tile::Label(y + 1)];
+
+            for x in 0..6 {
+                row.push(match self.field[x][y] {
+                    None => Tile::Empty,
+                    Some(p) => Tile::Piece(p),
+                });
+                if x == 2 {
+                    row.push(Tile::Border);
+                } else if x != 5 {
+                    row.push(Tile::Space);
+                }
+            }
+
+            row.push(Tile::Label(y + 1));
+            grid.push(row);
+
+            if y != 5 {
+                let mut row = vec![Tile::Border];
+                for x in 0..11 {
+                    row.push(if y == 2 || x == 5 {
+                        Tile::Border
+                    } else {
+                        Tile::Space
+                    });
+                }
+                row.push(Tile::Border);
+                grid.push(row);
+            }
+        }
+
+        grid.push(hor_border);
+
+        let layout = vec![
+            vec![Tile::Label(1), Tile::Label(2)],
+            vec![Tile::Label(3), Tile::Label(4)],
+        ];
+
+        let line = util::find_row(6, 6, &|x, y| self.field[x][y], 5)
+            .map(|row| vec![grid_pos(row[0]), grid_pos(row[4])])
+            .unwrap_or_default();
+
+        Board {
+            parts: vec![
+                Part::Grid(grid),
+                Part::Text(String::from("\nSubfield layout:")),
+                Part::Grid(layout),
+            ],
+            border: Color::Blue,
+            highlight: Vec::new(),
+            line,
+        }
+    }
+
+    fn move_tiles(&self, _: &PMove) -> Vec<(usize, usize)> {
+        self.last.map(grid_pos).into_iter().collect()
+    }
+
+    fn describe_move(&self, mov: &PMove) -> String {
+        let dir = if mov.dir == ANTI_CLOCKWISE {
+            "anticlockwise"
+        } else {
+            "clockwise"
+        };
+        format!(
+            "placed a marble on ({}, {}) and rotated subfield {} {}",
+            mov.x + 1,
+            mov.y + 1,
+            mov.sel + 1,
+            dir
+        )
+    }
+
+    fn parse_move(text: &str) -> Option<PMove> {
+        text.trim().parse().ok()
+    }
+
+    fn make_move(&mut self, mov: PMove, person: usize) -> GameState {
+        if mov.sel >= 4 || mov.x >= 6 || mov.y >= 6 || self.field[mov.x][mov.y].is_some() {
+            return GameState::Invalid;
+        }
+
+        self.field[mov.x][mov.y] = Some(person);
+        self.last = Some((mov.x, mov.y));
+
+        if let GameState::Win(_) = self.status() {
+            return self.status();
+        }
+
+        // permutation table
+        let mut perm = [
+            [(1, 0), (0, 1), (1, 2), (2, 1)],
+            [(0, 0), (0, 2), (2, 2), (2, 0)],
+        ];
+
+        // offsets of the sub-fields
+        let offset = [(0, 0), (3, 0), (0, 3), (3, 3)];
+
+        for perm in perm.iter_mut() {
+            for perm in perm.iter_mut() {
+                let off = offset[mov.sel];
+                *perm = (perm.0 + off.0, perm.1 + off.1);
+            }
+        }
+
+        for _ in 0..mov.dir {
+            for perm in perm.iter() {
+                let mut last = self.field[perm[3].0][perm[3].1];
+                for (x, y) in perm.iter() {
+                    let new_last = self.field[*x][*y];
+                    self.field[*x][*y] = last;
+                    last = new_last;
+                }
+                if let Some(k) = perm.iter().position(|&pos| Some(pos) == self.last) {
+                    self.last = Some(perm[(k + 1) % 4]);
+                }
+            }
+        }
+
+        self.status()
+    }
+
+    fn status(&self) -> GameState {
+        util::n_in_a_row(6, 6, &|x, y| self.field[x][y], 5)
+    }
+
+    fn pieces() -> [Piece; 2] {
+        [
+            Piece(Shape::Circle, Color::Yellow),
+            Piece(Shape::Circle, Color::Red),
+        ]
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
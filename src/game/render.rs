@@ -0,0 +1,205 @@
+//! Turning the board of a game into text
+
+use super::util::*;
+use std::str::FromStr;
+
+/// A single square of a drawn board
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Tile {
+    /// A cell without a piece
+    Empty,
+    /// Filler between cells that can't be played on
+    Space,
+    /// Separates parts of the board
+    Border,
+    /// A piece of the player with the index
+    Piece(usize),
+    /// Belongs to neither player, like a tied field in ultimate tic tac toe
+    Neutral,
+    /// A number, like a coordinate or the number to react with for a move
+    Label(usize),
+}
+
+pub enum Part {
+    Grid(Vec<Vec<Tile>>),
+    Text(String),
+}
+
+/// Everything that needs to be displayed of a game
+pub struct Board {
+    pub parts: Vec<Part>,
+    /// Color of the border tiles when drawn with emojis
+    pub border: Color,
+    /// Tiles of the first grid that show the last move
+    pub highlight: Vec<(usize, usize)>,
+    /// Tiles of the first grid that the winning line goes through, from one end to the other
+    pub line: Vec<(usize, usize)>,
+}
+
+impl Board {
+    pub fn new(grid: Vec<Vec<Tile>>, border: Color) -> Self {
+        Self {
+            parts: vec![Part::Grid(grid)],
+            border,
+            highlight: Vec::new(),
+            line: Vec::new(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Shape {
+    Square,
+    Circle,
+}
+
+/// How the pieces of a player look like
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Piece(pub Shape, pub Color);
+
+pub trait Renderer {
+    /// Displays the piece of a player
+    fn piece(&self, pieces: &[Piece; 2], player: usize) -> String;
+    fn render(&self, board: &Board, pieces: &[Piece; 2]) -> String;
+}
+
+/// Draws boards with colored emoji squares and keycap digits
+pub struct Emoji;
+
+impl Renderer for Emoji {
+    fn piece(&self, pieces: &[Piece; 2], player: usize) -> String {
+        match pieces[player] {
+            Piece(Shape::Square, color) => square(color),
+            Piece(Shape::Circle, color) => circle(color),
+        }
+        .into()
+    }
+
+    fn render(&self, board: &Board, pieces: &[Piece; 2]) -> String {
+        let mut parts = Vec::new();
+        for part in board.parts.iter() {
+            match part {
+                Part::Text(text) => parts.push(text.clone()),
+                Part::Grid(grid) => {
+                    let mut rows = Vec::new();
+                    for row in grid.iter() {
+                        let mut line = String::new();
+                        for &tile in row.iter() {
+                            line += &match tile {
+                                Tile::Empty | Tile::Space => square(Color::Black).into(),
+                                Tile::Border => square(board.border).into(),
+                                Tile::Neutral => square(Color::Blue).into(),
+                                Tile::Piece(p) => self.piece(pieces, p),
+                                Tile::Label(num) => NUMBERS[num].clone(),
+                            };
+                        }
+                        rows.push(line);
+                    }
+                    parts.push(rows.join("\n"));
+                }
+            }
+        }
+        parts.join("\n")
+    }
+}
+
+/// Draws boards with plain ASCII characters, intended for a monospace font
+pub struct Ascii;
+
+impl Ascii {
+    fn border(grid: &[Vec<Tile>], x: usize, y: usize) -> char {
+        let is_border =
+            |x: usize, y: usize| grid.get(y).and_then(|row| row.get(x)) == Some(&Tile::Border);
+        let hor = (x > 0 && is_border(x - 1, y)) || is_border(x + 1, y);
+        let ver = (y > 0 && is_border(x, y - 1)) || is_border(x, y + 1);
+        match (hor, ver) {
+            (true, true) => '+',
+            (true, false) => '-',
+            (false, true) => '|',
+            (false, false) => ' ',
+        }
+    }
+}
+
+impl Renderer for Ascii {
+    fn piece(&self, _: &[Piece; 2], player: usize) -> String {
+        ["X", "O"][player].into()
+    }
+
+    fn render(&self, board: &Board, pieces: &[Piece; 2]) -> String {
+        let mut parts = Vec::new();
+        for part in board.parts.iter() {
+            match part {
+                Part::Text(text) => parts.push(text.clone()),
+                Part::Grid(grid) => {
+                    let mut rows = Vec::new();
+                    for (y, row) in grid.iter().enumerate() {
+                        let mut line = String::new();
+                        let mut last = ' ';
+                        for (x, &tile) in row.iter().enumerate() {
+                            let ch = match tile {
+                                Tile::Empty => '.',
+                                Tile::Space => ' ',
+                                Tile::Border => Self::border(grid, x, y),
+                                Tile::Neutral => '#',
+                                Tile::Piece(p) => self.piece(pieces, p).remove(0),
+                                Tile::Label(num) => (b'0' + num as u8) as char,
+                            };
+                            if x > 0 {
+                                // connect horizontal lines
+                                let connected = "-+".contains(last) && "-+".contains(ch);
+                                line.push(if connected { '-' } else { ' ' });
+                            }
+                            line.push(ch);
+                            last = ch;
+                        }
+                        rows.push(line.trim_end().to_string());
+                    }
+                    parts.push(rows.join("\n"));
+                }
+            }
+        }
+        parts.join("\n")
+    }
+}
+
+/// The renderers that players can choose from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Style {
+    #[default]
+    Emoji,
+    Ascii,
+    /// The board is drawn as an image, see the `image` module
+    Image,
+}
+
+impl Style {
+    /// The renderer for text, images use emojis for the text around the board.
+    pub fn renderer(&self) -> &'static dyn Renderer {
+        match self {
+            Self::Emoji | Self::Image => &Emoji,
+            Self::Ascii => &Ascii,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Emoji => "emoji",
+            Self::Ascii => "ascii",
+            Self::Image => "image",
+        }
+    }
+}
+
+impl FromStr for Style {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_lowercase().as_str() {
+            "emoji" => Ok(Self::Emoji),
+            "ascii" | "text" => Ok(Self::Ascii),
+            "image" | "png" => Ok(Self::Image),
+            _ => Err("unknown style, use `emoji`, `ascii` or `image`"),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use super::*;
+use cartesian::*;
+
+pub type TTTField = [Option<usize>; 9];
+
+pub const FIELD_NAMES: [&str; 9] = [
+    "top left",
+    "top",
+    "top right",
+    "left",
+    "center",
+    "right",
+    "bottom left",
+    "bottom",
+    "bottom right",
+];
+
+impl PvpGame<usize> for TTTField {
+    fn title() -> &'static str {
+        "Tic Tac Toe"
+    }
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+    fn status(&self) -> GameState {
+        if let Some(combo) = winning_combo(self) {
+            return GameState::Win(self[combo[0]].unwrap());
+        }
+        if self.iter().all(|e| e.is_some()) {
+            GameState::Tie
+        } else {
+            GameState::Running
+        }
+    }
+    fn make_move(&mut self, idx: usize, person: usize) -> GameState {
+        if idx >= self.len() {
+            GameState::Invalid
+        } else if self[idx].is_some() {
+            GameState::Invalid
+        } else {
+            self[idx] = Some(person);
+            self.status()
+        }
+    }
+    fn parse_move(text: &str) -> Option<usize> {
+        parse_label(text, 1..10)
+    }
+    fn pieces() -> [Piece; 2] {
+        [
+            Piece(Shape::Square, Color::Red),
+            Piece(Shape::Square, Color::Yellow),
+        ]
+    }
+    fn board(&self) -> Board {
+        let grid = nine_fields(&|o, i| match self[o] {
+            Some(p) => Tile::Piece(p),
+            None if i == 4 => Tile::Label(o + 1),
+            None => Tile::Space,
+        });
+        let mut board = Board::new(grid, Color::White);
+        if let Some(combo) = winning_combo(self) {
+            board.line = vec![field_center(combo[0]), field_center(combo[2])];
+        }
+        board
+    }
+    fn describe_move(&self, &idx: &usize) -> String {
+        format!("marked field {} ({})", idx + 1, FIELD_NAMES[idx])
+    }
+    fn move_tiles(&self, &idx: &usize) -> Vec<(usize, usize)> {
+        (0..9).map(|i| field_tile(idx, i)).collect()
+    }
+    fn ai() -> Option<Box<dyn AiPlayer<usize, Self> + Send + Sync>> {
+        Some(Box::new(Minimax(TTTAI)))
+    }
+    fn possible_moves(&self, _: usize) -> Vec<usize> {
+        (0..9).filter(|&i| self[i].is_none()).collect()
+    }
+}
+
+pub struct TTTAI;
+
+impl MinimaxAi<TTTField> for TTTAI {
+    fn rate(&self, _: &TTTField, _: usize) -> f64 {
+        0.0
+    }
+    fn default_move(&self) -> usize {
+        0
+    }
+    fn depth(&self) -> usize {
+        9
+    }
+}
+
+pub fn flatten_xy(x: usize, y: usize) -> usize {
+    3 * y + x
+}
+
+/// Lays out a 3x3 grid of fields with borders in between,
+/// where each field is drawn as 3x3 tiles.
+///
+/// `tile` gets the index of the field and the index of the tile inside of the field.
+pub fn nine_fields(tile: &dyn Fn(usize, usize) -> Tile) -> Vec<Vec<Tile>> {
+    let mut grid = vec![vec![Tile::Border; 11]; 11];
+    for (x, y) in cartesian!(0..11, 0..11) {
+        if x % 4 != 3 && y % 4 != 3 {
+            let outer = flatten_xy(x / 4, y / 4);
+            let inner = flatten_xy(x % 4, y % 4);
+            grid[y][x] = tile(outer, inner);
+        }
+    }
+    grid
+}
+
+/// position of a tile in the grid of `nine_fields`
+pub fn field_tile(field: usize, tile: usize) -> (usize, usize) {
+    (4 * (field % 3) + tile % 3, 4 * (field / 3) + tile / 3)
+}
+
+pub fn field_center(field: usize) -> (usize, usize) {
+    field_tile(field, 4)
+}
+
+/// the three fields in a row, column or diagonal that are marked by the same player
+pub fn winning_combo(field: &TTTField) -> Option<[usize; 3]> {
+    let mut win_combos = vec![[0, 4, 8], [2, 4, 6]];
+    for i in 0..3 {
+        let i3 = 3 * i;
+        win_combos.push([i, i + 3, i + 6]);
+        win_combos.push([i3, i3 + 1, i3 + 2]);
+    }
+    win_combos.into_iter().find(|combo| {
+        field[combo[0]].is_some() && (0..3).all(|i| field[combo[i]] == field[combo[0]])
+    })
+}
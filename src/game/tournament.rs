@@ -0,0 +1,441 @@
+//! Pairings and standings of tournaments
+//!
+//! Players are numbered by their seed, the best player is `0`. Front ends map these numbers to
+//! the actual players, play the games and record the outcomes.
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Players with the same score play against each other, nobody is eliminated
+    Swiss,
+    /// Everybody plays against everybody once
+    RoundRobin,
+    /// The loser of a game is eliminated, a draw advances the better seed
+    Knockout,
+}
+
+impl Format {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Swiss => "swiss",
+            Self::RoundRobin => "roundrobin",
+            Self::Knockout => "knockout",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_lowercase().as_str() {
+            "swiss" => Ok(Self::Swiss),
+            "roundrobin" | "round-robin" | "rr" => Ok(Self::RoundRobin),
+            "knockout" | "ko" => Ok(Self::Knockout),
+            _ => Err("unknown format, use `swiss`, `roundrobin` or `knockout`"),
+        }
+    }
+}
+
+/// Result of a game, from the view of the first player
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Outcome {
+    /// points of the first player
+    fn points(&self) -> f64 {
+        match self {
+            Self::Win => 1.0,
+            Self::Draw => 0.5,
+            Self::Loss => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pairing {
+    pub round: usize,
+    /// The player that moves first
+    pub first: usize,
+    /// `None` is a bye, which counts as a win
+    pub second: Option<usize>,
+    pub outcome: Option<Outcome>,
+}
+
+impl Pairing {
+    fn new(round: usize, first: usize, second: Option<usize>) -> Self {
+        let outcome = match second {
+            Some(_) => None,
+            None => Some(Outcome::Win),
+        };
+        Self {
+            round,
+            first,
+            second,
+            outcome,
+        }
+    }
+
+    /// points and opponent of a player in this game
+    fn result_of(&self, player: usize) -> Option<(f64, Option<usize>)> {
+        let points = self.outcome?.points();
+        if self.first == player {
+            Some((points, self.second))
+        } else if self.second == Some(player) {
+            Some((1.0 - points, Some(self.first)))
+        } else {
+            None
+        }
+    }
+
+    /// who advances in a knockout tournament
+    fn advancing(&self) -> Option<usize> {
+        match (self.outcome?, self.second) {
+            (_, None) | (Outcome::Win, _) => Some(self.first),
+            (Outcome::Loss, second) => second,
+            (Outcome::Draw, Some(second)) => Some(self.first.min(second)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub player: usize,
+    pub score: f64,
+    /// Sum of the scores of all opponents
+    pub buchholz: f64,
+    /// Sum of the scores of the beaten opponents and half of the scores of the drawn ones
+    pub sonneborn_berger: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    format: Format,
+    players: usize,
+    pairings: Vec<Pairing>,
+}
+
+impl Tournament {
+    pub fn new(format: Format, players: usize) -> Self {
+        Self {
+            format,
+            players,
+            pairings: Vec::new(),
+        }
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn pairings(&self) -> &[Pairing] {
+        &self.pairings
+    }
+
+    /// Number of rounds that are played in total
+    pub fn rounds(&self) -> usize {
+        let log2 = bracket_size(self.players).trailing_zeros() as usize;
+        match self.format {
+            // with an odd number of players, everybody has a bye once
+            Format::RoundRobin => self.players - 1 + self.players % 2,
+            _ => log2.max(1),
+        }
+    }
+
+    /// Number of rounds that have been paired so far
+    pub fn round(&self) -> usize {
+        self.pairings.last().map_or(0, |p| p.round + 1)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.round() == self.rounds() && self.pairings.iter().all(|p| p.outcome.is_some())
+    }
+
+    /// Records the outcome of a game, the index is the one of `pairings`
+    pub fn record(&mut self, pairing: usize, outcome: Outcome) {
+        self.pairings[pairing].outcome = Some(outcome);
+    }
+
+    /// Pairs the players for the next round and returns the indices of the new pairings.
+    ///
+    /// Returns `None` if the current round isn't finished yet or the tournament is over.
+    pub fn next_round(&mut self) -> Option<std::ops::Range<usize>> {
+        if self.pairings.iter().any(|p| p.outcome.is_none()) || self.round() >= self.rounds() {
+            return None;
+        }
+
+        let round = self.round();
+        let pairs = match self.format {
+            Format::Swiss => self.swiss_pairs(),
+            Format::RoundRobin => self.round_robin_pairs(round),
+            Format::Knockout => self.knockout_pairs(round),
+        };
+
+        let start = self.pairings.len();
+        self.pairings.extend(
+            pairs
+                .into_iter()
+                .map(|(first, second)| Pairing::new(round, first, second)),
+        );
+        Some(start..self.pairings.len())
+    }
+
+    /// Circle method: the first player stays in place, the others rotate every round
+    fn round_robin_pairs(&self, round: usize) -> Vec<(usize, Option<usize>)> {
+        // with an odd number of players, the one paired with the missing player has a bye
+        let n = self.players + self.players % 2;
+        let seat = |i: usize| match i {
+            0 => 0,
+            _ => 1 + (i - 1 + round) % (n - 1),
+        };
+
+        (0..n / 2)
+            .map(|i| {
+                let (mut a, mut b) = (seat(i), seat(n - 1 - i));
+                // alternate who moves first
+                if round % 2 == 1 {
+                    std::mem::swap(&mut a, &mut b);
+                }
+                match (a < self.players, b < self.players) {
+                    (true, true) => (a, Some(b)),
+                    (true, false) => (a, None),
+                    _ => (b, None),
+                }
+            })
+            .collect()
+    }
+
+    /// Players with similar scores play against each other, without rematches if possible
+    fn swiss_pairs(&self) -> Vec<(usize, Option<usize>)> {
+        let mut ranking = (0..self.players).collect::<Vec<_>>();
+        ranking.sort_by(|&a, &b| {
+            self.score(b)
+                .partial_cmp(&self.score(a))
+                .unwrap_or(Ordering::Equal)
+                .then(a.cmp(&b))
+        });
+
+        let mut pairs = Vec::new();
+
+        // the lowest ranked player that didn't have a bye yet gets one
+        if ranking.len() % 2 == 1 {
+            let had_bye = |p: usize| {
+                self.pairings
+                    .iter()
+                    .any(|pairing| pairing.first == p && pairing.second.is_none())
+            };
+            let idx = ranking
+                .iter()
+                .rposition(|&p| !had_bye(p))
+                .unwrap_or(ranking.len() - 1);
+            pairs.push((ranking.remove(idx), None));
+        }
+
+        let played = |a: usize, b: usize| {
+            self.pairings.iter().any(|p| {
+                (p.first == a && p.second == Some(b)) || (p.first == b && p.second == Some(a))
+            })
+        };
+
+        let matched = pair_up(&ranking, &played)
+            .or_else(|| pair_up(&ranking, &|_, _| false))
+            .unwrap_or_default();
+        pairs.extend(matched.into_iter().map(|(a, b)| (a, Some(b))));
+        pairs
+    }
+
+    /// The first round is seeded so the best players meet last, later rounds pair the winners
+    fn knockout_pairs(&self, round: usize) -> Vec<(usize, Option<usize>)> {
+        if round == 0 {
+            let seeds = bracket(bracket_size(self.players));
+            return seeds
+                .chunks(2)
+                .map(|pair| match pair[1] < self.players {
+                    true => (pair[0], Some(pair[1])),
+                    false => (pair[0], None),
+                })
+                .collect();
+        }
+
+        let winners = self
+            .pairings
+            .iter()
+            .filter(|p| p.round == round - 1)
+            .filter_map(Pairing::advancing)
+            .collect::<Vec<_>>();
+        winners
+            .chunks(2)
+            .map(|pair| (pair[0], pair.get(1).copied()))
+            .collect()
+    }
+
+    pub fn score(&self, player: usize) -> f64 {
+        self.pairings
+            .iter()
+            .filter_map(|p| p.result_of(player))
+            .map(|(points, _)| points)
+            .sum()
+    }
+
+    /// All players, ordered by score and tiebreaks
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings = (0..self.players)
+            .map(|player| {
+                let results = self
+                    .pairings
+                    .iter()
+                    .filter_map(|p| p.result_of(player))
+                    .filter_map(|(points, opponent)| Some((points, self.score(opponent?))))
+                    .collect::<Vec<_>>();
+                Standing {
+                    player,
+                    score: self.score(player),
+                    buchholz: results.iter().map(|(_, score)| score).sum(),
+                    sonneborn_berger: results.iter().map(|(points, score)| points * score).sum(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let key = |s: &Standing| (s.score, s.buchholz, s.sonneborn_berger);
+        standings.sort_by(|a, b| {
+            key(b)
+                .partial_cmp(&key(a))
+                .unwrap_or(Ordering::Equal)
+                .then(a.player.cmp(&b.player))
+        });
+        standings
+    }
+}
+
+/// Smallest power of two that fits all players
+fn bracket_size(players: usize) -> usize {
+    players.max(1).next_power_of_two()
+}
+
+/// Seeds in bracket order, neighbours play against each other in the first round
+fn bracket(size: usize) -> Vec<usize> {
+    if size <= 1 {
+        return vec![0];
+    }
+    bracket(size / 2)
+        .into_iter()
+        .flat_map(|seed| vec![seed, size - 1 - seed])
+        .collect()
+}
+
+/// Pairs the players from top to bottom, backtracking when two players can't play each other
+fn pair_up(
+    players: &[usize],
+    forbidden: &dyn Fn(usize, usize) -> bool,
+) -> Option<Vec<(usize, usize)>> {
+    let (&first, rest) = match players.split_first() {
+        Some(split) => split,
+        None => return Some(Vec::new()),
+    };
+    for (idx, &second) in rest.iter().enumerate() {
+        if forbidden(first, second) {
+            continue;
+        }
+        let mut others = rest.to_vec();
+        others.remove(idx);
+        if let Some(mut pairs) = pair_up(&others, forbidden) {
+            pairs.insert(0, (first, second));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+#[test]
+fn round_robin_test() {
+    let mut tournament = Tournament::new(Format::RoundRobin, 5);
+    while let Some(round) = tournament.next_round() {
+        for idx in round {
+            if tournament.pairings()[idx].second.is_some() {
+                tournament.record(idx, Outcome::Draw);
+            }
+        }
+    }
+    assert!(tournament.is_finished());
+
+    // everybody played everybody else exactly once, and had one bye
+    for a in 0..5 {
+        for b in 0..5 {
+            let games = tournament
+                .pairings()
+                .iter()
+                .filter(|p| {
+                    (p.first, p.second) == (a, Some(b)) || (p.first, p.second) == (b, Some(a))
+                })
+                .count();
+            assert_eq!(games, if a == b { 0 } else { 1 });
+        }
+        assert_eq!(tournament.score(a), 3.0);
+    }
+}
+
+#[test]
+fn swiss_test() {
+    let mut tournament = Tournament::new(Format::Swiss, 6);
+    while let Some(round) = tournament.next_round() {
+        for idx in round {
+            // the better seed always wins
+            let p = &tournament.pairings()[idx];
+            let outcome = if Some(p.first) < p.second {
+                Outcome::Win
+            } else {
+                Outcome::Loss
+            };
+            tournament.record(idx, outcome);
+        }
+    }
+    assert_eq!(tournament.rounds(), 3);
+
+    // no rematches
+    let mut games = tournament
+        .pairings()
+        .iter()
+        .map(|p| {
+            (
+                p.first.min(p.second.unwrap()),
+                p.first.max(p.second.unwrap()),
+            )
+        })
+        .collect::<Vec<_>>();
+    games.sort();
+    games.dedup();
+    assert_eq!(games.len(), 9);
+
+    let standings = tournament.standings();
+    assert_eq!(standings[0].player, 0);
+    assert_eq!(standings[0].score, 3.0);
+}
+
+#[test]
+fn knockout_test() {
+    let mut tournament = Tournament::new(Format::Knockout, 5);
+    let first = tournament.next_round().unwrap();
+    // the three best seeds have a bye
+    let pairings = &tournament.pairings()[first];
+    assert_eq!(pairings.iter().filter(|p| p.second.is_none()).count(), 3);
+    assert_eq!((pairings[1].first, pairings[1].second), (3, Some(4)));
+    tournament.record(1, Outcome::Loss);
+
+    while let Some(round) = tournament.next_round() {
+        for idx in round {
+            if tournament.pairings()[idx].second.is_some() {
+                tournament.record(idx, Outcome::Draw);
+            }
+        }
+    }
+    assert!(tournament.is_finished());
+    let last = tournament.pairings().last().unwrap();
+    assert_eq!((last.first, last.second), (0, Some(1)));
+    assert_eq!(tournament.standings()[0].player, 0);
+}
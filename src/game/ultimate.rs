@@ -1,22 +1,30 @@
 use super::tictactoe::*;
 use super::*;
 
+/// Seconds the analysis spends on each position
+const ANALYSIS_TIME: f64 = 0.3;
+
 #[derive(Clone, PartialEq, Eq, Default)]
 pub struct UltimateGame {
     field: [TTTField; 9],
     cell: usize,
+    /// field and tile of the last move
+    last: Option<(usize, usize)>,
 }
 
 impl PvpGame<usize> for UltimateGame {
     fn title() -> &'static str {
         "Ultimate Tic Tac Toe"
     }
-    fn figures() -> Vec<String> {
-        TTTField::figures()
+    fn pieces() -> [Piece; 2] {
+        TTTField::pieces()
     }
     fn ai() -> Option<Box<dyn AiPlayer<usize, Self> + Send + Sync>> {
         Some(Box::new(RandomPlayer::<Self>::default()))
     }
+    fn analysis_ai() -> Option<Box<dyn AiPlayer<usize, Self> + Send + Sync>> {
+        Some(Box::new(mcts::TreeSearchAi::new(ANALYSIS_TIME)))
+    }
     fn possible_moves(&self, player: usize) -> Vec<usize> {
         self.field[self.cell].possible_moves(player)
     }
@@ -24,18 +32,10 @@ impl PvpGame<usize> for UltimateGame {
         *self == Self::default()
     }
     fn status(&self) -> GameState {
-        let mut wins = [None; 9];
-        for i in 0..9 {
-            wins[i] = match self.field[i].status() {
-                GameState::Win(p) => Some(p),
-                GameState::Tie => Some(42),
-                _ => None,
-            }
-        }
-        wins.status()
+        self.wins().status()
     }
-    fn input() -> Box<dyn InputMethod<usize> + Send + Sync> {
-        Box::new(ReactionInput((1..10).map(number_emoji).collect()))
+    fn parse_move(text: &str) -> Option<usize> {
+        parse_label(text, 1..10)
     }
     fn make_move(&mut self, pos: usize, player: usize) -> GameState {
         if self.field[self.cell][pos].is_some() {
@@ -43,6 +43,7 @@ impl PvpGame<usize> for UltimateGame {
         }
 
         self.field[self.cell][pos] = Some(player);
+        self.last = Some((self.cell, pos));
         self.cell = pos;
 
         // find next playable field
@@ -61,40 +62,35 @@ impl PvpGame<usize> for UltimateGame {
 
         self.status()
     }
-    fn draw(&self) -> String {
-        let mut field = String::new();
-        for y in 0..3 {
-            for iy in 0..3 {
-                for x in 0..3 {
-                    for ix in 0..3 {
-                        let o = flatten_xy(x, y);
-                        let i = flatten_xy(ix, iy);
-                        let fig = Self::figures();
-                        let sym = match self.field[o].status() {
-                            GameState::Win(p) => &fig[p],
-                            GameState::Tie => TIE,
-                            _ => match self.field[o][i] {
-                                Some(p) => &fig[p],
-                                _ if o == self.cell => &NUMBERS[i + 1],
-                                _ => EMPTY,
-                            },
-                        };
-                        field += sym;
-                    }
-                    if x < 2 {
-                        field += BORDER;
-                    }
-                }
-                field += "\n";
-            }
-            if y < 2 {
-                for _ in 0..3 * 4 - 1 {
-                    field += BORDER;
-                }
-            }
-            field += "\n";
+    fn board(&self) -> Board {
+        let grid = nine_fields(&|o, i| match self.field[o].status() {
+            GameState::Win(p) => Tile::Piece(p),
+            GameState::Tie => Tile::Neutral,
+            _ => match self.field[o][i] {
+                Some(p) => Tile::Piece(p),
+                _ if o == self.cell => Tile::Label(i + 1),
+                _ => Tile::Empty,
+            },
+        });
+        let mut board = Board::new(grid, Color::White);
+        if let Some(combo) = winning_combo(&self.wins()) {
+            board.line = vec![field_center(combo[0]), field_center(combo[2])];
         }
-        field
+        board
+    }
+    fn move_tiles(&self, _: &usize) -> Vec<(usize, usize)> {
+        self.last
+            .map(|(o, i)| field_tile(o, i))
+            .into_iter()
+            .collect()
+    }
+    fn describe_move(&self, &pos: &usize) -> String {
+        format!(
+            "marked field {} ({}) of the {} subfield",
+            pos + 1,
+            FIELD_NAMES[pos],
+            FIELD_NAMES[self.cell]
+        )
     }
 }
 
@@ -103,7 +99,21 @@ impl UltimateGame {
         Self {
             field: Default::default(),
             cell: 0,
+            last: None,
+        }
+    }
+
+    /// the big field, where every finished small field counts as a mark
+    fn wins(&self) -> TTTField {
+        let mut wins = [None; 9];
+        for i in 0..9 {
+            wins[i] = match self.field[i].status() {
+                GameState::Win(p) => Some(p),
+                GameState::Tie => Some(42),
+                _ => None,
+            }
         }
+        wins
     }
 }
 
@@ -1,5 +1,4 @@
 use super::GameState;
-use crate::ser::*;
 use cartesian::*;
 
 pub fn n_in_a_row(
@@ -8,12 +7,29 @@ pub fn n_in_a_row(
     index: &dyn Fn(usize, usize) -> Option<usize>,
     n: usize,
 ) -> GameState {
+    if let Some(row) = find_row(rows, cols, index, n) {
+        let (x, y) = row[0];
+        return GameState::Win(index(x, y).unwrap());
+    }
+
+    let filled = cartesian!(0..cols, 0..rows).all(|(x, y)| index(x, y).is_some());
+    if filled {
+        GameState::Tie
+    } else {
+        GameState::Running
+    }
+}
+
+/// finds `n` pieces of the same color in a row, column or diagonal and returns their positions
+pub fn find_row(
+    rows: usize,
+    cols: usize,
+    index: &dyn Fn(usize, usize) -> Option<usize>,
+    n: usize,
+) -> Option<Vec<(usize, usize)>> {
     let n = n as isize;
-    let mut filled = true;
 
     for (x, y) in cartesian!(0..cols, 0..rows) {
-        filled &= index(x, y).is_some();
-
         let color = match index(x, y) {
             Some(color) => color,
             None => continue,
@@ -23,6 +39,7 @@ pub fn n_in_a_row(
             if dx == 0 && dy == 0 {
                 continue;
             }
+            let mut row = vec![(x, y)];
             for i in 1..n {
                 let x = i * dx + x as isize;
                 let y = i * dy + y as isize;
@@ -32,14 +49,21 @@ pub fn n_in_a_row(
                 if index(x as _, y as _) != Some(color) {
                     continue 'dir;
                 }
+                row.push((x as _, y as _));
             }
-            return GameState::Win(color);
+            return Some(row);
         }
     }
-    if filled {
-        GameState::Tie
+    None
+}
+
+/// parses a number that is displayed as a label, returning its index in the labels
+pub fn parse_label(text: &str, labels: std::ops::Range<usize>) -> Option<usize> {
+    let num = text.trim().parse::<usize>().ok()?;
+    if labels.contains(&num) {
+        Some(num - labels.start)
     } else {
-        GameState::Running
+        None
     }
 }
 
@@ -51,10 +75,6 @@ lazy_static! {
         .collect();
 }
 
-pub fn number_emoji(num: usize) -> ReactionType {
-    ReactionType::Unicode(NUMBERS[num].clone())
-}
-
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum Color {
     White,
@@ -68,6 +88,23 @@ pub enum Color {
     Brown,
 }
 
+impl Color {
+    /// the color of the emoji, for drawing images
+    pub fn rgb(&self) -> [u8; 3] {
+        match self {
+            Self::White => [230, 231, 232],
+            Self::Black => [49, 55, 61],
+            Self::Red => [221, 46, 68],
+            Self::Orange => [244, 144, 12],
+            Self::Yellow => [253, 203, 88],
+            Self::Green => [120, 177, 89],
+            Self::Blue => [85, 172, 238],
+            Self::Purple => [170, 142, 214],
+            Self::Brown => [193, 105, 79],
+        }
+    }
+}
+
 pub fn square(color: Color) -> &'static str {
     match color {
         Color::White => "⬜",
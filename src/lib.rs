@@ -0,0 +1,8 @@
+//! Parts of the bot that don't depend on discord and can be reused by other front ends.
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod brainfuck;
+pub mod esolang;
+pub mod game;
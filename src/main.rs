@@ -1,3 +1,7 @@
+// The standard framework is deprecated since serenity 0.12, but it still works fine for the
+// prefix commands of this bot.
+#![allow(deprecated)]
+
 #[macro_use]
 extern crate lazy_static;
 
@@ -5,9 +9,10 @@ mod commands;
 mod prelude;
 mod ser;
 mod util;
+mod worker;
 
 use crate::ser::*;
-use commands::{brainfuck::*, easteregg::*, meta::*, owner::*, util::*};
+use commands::{brainfuck::*, config, easteregg::*, meta::*, owner::*, slash, util::*};
 use prelude::*;
 use std::{collections::HashSet, env, sync::Arc};
 use tracing::*;
@@ -16,7 +21,24 @@ use tracing_subscriber::{EnvFilter, FmtSubscriber};
 pub struct ShardManagerContainer;
 
 impl TypeMapKey for ShardManagerContainer {
-    type Value = Arc<Mutex<ShardManager>>;
+    type Value = Arc<ShardManager>;
+}
+
+/// The framework, so slash commands can dispatch the prefix commands they stand for
+pub struct FrameworkContainer;
+
+impl TypeMapKey for FrameworkContainer {
+    type Value = Arc<StandardFramework>;
+}
+
+/// Passes events to a framework that is shared with `FrameworkContainer`
+struct SharedFramework(Arc<StandardFramework>);
+
+#[async_trait]
+impl Framework for SharedFramework {
+    async fn dispatch(&self, ctx: Context, event: FullEvent) {
+        self.0.dispatch(ctx, event).await;
+    }
 }
 
 struct Handler;
@@ -25,7 +47,28 @@ struct Handler;
 impl EventHandler for Handler {
     async fn ready(&self, ctx: Context, ready: Ready) {
         info!("Connected as {}", ready.user.name);
-        ctx.shard.set_activity(Some(Activity::listening("=help")));
+        ctx.shard
+            .set_activity(Some(ActivityData::listening("=help")));
+
+        if let Err(why) = commands::play::resume_games(&ctx) {
+            error!("Could not resume games: {:?}", why);
+        }
+
+        let commands = slash::create_commands();
+        if let Err(why) = SlashCommand::set_global_commands(&ctx.http, commands).await {
+            error!("Could not register slash commands: {:?}", why);
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let result = match &interaction {
+            Interaction::Command(command) => slash::run(&ctx, command).await,
+            Interaction::Autocomplete(command) => slash::autocomplete(&ctx, command).await,
+            _ => Ok(()),
+        };
+        if let Err(why) = result {
+            info!("Slash command failed: {}", why);
+        }
     }
 
     async fn resume(&self, _: Context, _: ResumedEvent) {
@@ -46,19 +89,39 @@ async fn help(
     groups: &[&'static CommandGroup],
     owners: HashSet<UserId>,
 ) -> CommandResult {
-    help_commands::with_embeds(context, msg, args, help_options, groups, owners).await;
+    help_commands::with_embeds(context, msg, args, help_options, groups, owners).await?;
     Ok(())
 }
 
 #[hook]
-pub async fn on_dispatch_error(ctx: &Context, msg: &Message, _: DispatchError) {
+pub async fn on_dispatch_error(ctx: &Context, msg: &Message, _: DispatchError, _: &str) {
     let clown = ReactionType::Unicode("🤡".into());
     msg.react(ctx, clown).await.ok();
 }
 
+/// Channel where the bot ignored commands before servers could choose their ignored channels
+const LEGACY_IGNORED_CHANNEL: u64 = 819966095070330950;
+
 #[hook]
-pub async fn before(_ctx: &Context, msg: &Message, _cmd: &str) -> bool {
-    *msg.channel_id.as_u64() != 819966095070330950
+pub async fn before(_ctx: &Context, msg: &Message, cmd: &str) -> bool {
+    if msg.channel_id.get() == LEGACY_IGNORED_CHANNEL {
+        return false;
+    }
+
+    let server = match msg.guild_id {
+        Some(server) => server.get(),
+        None => return true,
+    };
+
+    let ignored = config::channels(server, config::Key::IgnoredChannels).unwrap_or_default();
+    let disabled = config::disabled_commands(server).unwrap_or_default();
+
+    !ignored.contains(&msg.channel_id) && !disabled.contains(&cmd)
+}
+
+#[hook]
+pub async fn dynamic_prefix(_ctx: &Context, msg: &Message) -> Option<String> {
+    config::prefix(msg.guild_id?.get()).ok().flatten()
 }
 
 #[hook]
@@ -91,7 +154,15 @@ pub fn command_groups() -> Vec<&'static CommandGroup> {
         &MANAGEMENT_GROUP,
         &GAMES_GROUP,
         &LEADERBOARD_GROUP,
+        &RATINGHISTORY_GROUP,
+        &ANALYSIS_GROUP,
+        &BOARDSTYLE_GROUP,
+        &TOURNAMENTS_GROUP,
+        &QUEUE_GROUP,
         &BRAINFUCK_GROUP,
+        &BRAINFUCKLIBRARY_GROUP,
+        &BRAINFUCKPROGRAMS_GROUP,
+        &config::CONFIG_GROUP,
     ]
 }
 
@@ -109,37 +180,53 @@ async fn main() {
 
     let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
 
-    let http = Http::new_with_token(&token);
+    let http = Http::new(&token);
 
     // We will fetch your bot's owners and id
     let (owners, _bot_id) = match http.get_current_application_info().await {
         Ok(info) => {
             let mut owners = HashSet::new();
-            owners.insert(info.owner.id);
+            if let Some(owner) = info.owner {
+                owners.insert(owner.id);
+            }
 
             (owners, info.id)
         }
         Err(why) => panic!("Could not access application info: {:?}", why),
     };
 
+    if let Err(why) = migrate_table() {
+        panic!("Could not migrate the brainfuck table: {:?}", why);
+    }
+
     // Create the framework
     let mut framework = StandardFramework::new()
         .help(&HELP)
-        .configure(|c| c.owners(owners).prefix(&PREFIX))
         .on_dispatch_error(on_dispatch_error)
         .before(before)
         .after(after)
-        .bucket("brainfuck", |b| b.time_span(10).limit(5))
+        .bucket("brainfuck", BucketBuilder::default().time_span(10).limit(5))
         .await
-        .bucket("game", |b| b.time_span(60).limit(6))
+        .bucket("game", BucketBuilder::default().time_span(60).limit(6))
         .await;
 
     for group in command_groups() {
         framework = framework.group(group);
     }
 
-    let mut client = Client::builder(&token)
-        .framework(framework)
+    framework.configure(
+        Configuration::new()
+            .owners(owners)
+            .prefix(*PREFIX)
+            .dynamic_prefix(dynamic_prefix),
+    );
+
+    let intents = GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT;
+
+    let framework = Arc::new(framework);
+
+    let mut client = Client::builder(&token, intents)
+        .framework(SharedFramework(framework.clone()))
         .event_handler(Handler)
         .await
         .expect("Err creating client");
@@ -147,6 +234,7 @@ async fn main() {
     {
         let mut data = client.data.write().await;
         data.insert::<ShardManagerContainer>(client.shard_manager.clone());
+        data.insert::<FrameworkContainer>(framework);
     }
 
     let shard_manager = client.shard_manager.clone();
@@ -155,7 +243,7 @@ async fn main() {
         tokio::signal::ctrl_c()
             .await
             .expect("Could not register ctrl+c handler");
-        shard_manager.lock().await.shutdown_all().await;
+        shard_manager.shutdown_all().await;
     });
 
     event!(tracing::Level::INFO, "Started bot at {}.", *START_DATE);
@@ -1,5 +1,6 @@
 #![allow(unused)]
 use super::command_groups;
+use crate::commands::config;
 use crate::ser::*;
 use async_trait::async_trait;
 use chrono::prelude::*;
@@ -43,7 +44,7 @@ pub fn commands() -> Vec<&'static Command> {
     cmds
 }
 
-fn command_names() -> Vec<&'static str> {
+pub fn command_names() -> Vec<&'static str> {
     commands()
         .iter()
         .flat_map(|c| c.options.names)
@@ -58,8 +59,13 @@ impl MessageArgs for Message {
     fn args(&self) -> Args {
         let delimiter = [Delimiter::Single(' ')];
 
-        // remove leading prefix
-        let content = self.content.clone().split_off(PREFIX.len());
+        // remove leading prefix, which is either the one of the server or the default one
+        let prefix = self
+            .guild_id
+            .and_then(|server| config::prefix(server.get()).ok().flatten())
+            .filter(|prefix| self.content.starts_with(prefix.as_str()))
+            .unwrap_or_else(|| PREFIX.to_string());
+        let content = self.content.clone().split_off(prefix.len());
 
         let mut args = Args::new(&content, &delimiter);
         let cmds = command_names();
@@ -106,7 +112,8 @@ pub fn split_into_fields(parts: &[String], default: &str) -> Vec<String> {
     fields
 }
 
-fn embed_template<'u, 'c: 'u>(msg: &'u Message, e: &'c mut CreateEmbed) -> &'c mut CreateEmbed {
+/// An empty embed with the footer and colour of the replies to the message
+pub fn embed_template(msg: &Message) -> CreateEmbed {
     let author = &msg.author;
 
     let name = msg
@@ -115,33 +122,31 @@ fn embed_template<'u, 'c: 'u>(msg: &'u Message, e: &'c mut CreateEmbed) -> &'c m
         .and_then(|m| m.nick.as_ref())
         .unwrap_or(&author.name);
 
-    e.footer(|f| {
-        author.avatar_url().map(|url| f.icon_url(url));
-        f.text(format!("summoned by {}", name))
-    });
+    let mut footer = CreateEmbedFooter::new(format!("summoned by {}", name));
+    if let Some(url) = author.avatar_url() {
+        footer = footer.icon_url(url);
+    }
 
-    e.color(Color::new(0x046B2F))
+    CreateEmbed::new()
+        .footer(footer)
+        .color(Color::new(0x046B2F))
 }
 
 #[async_trait]
 pub trait EmbedReply {
     async fn ereply<F>(&self, ctx: &Context, f: F) -> Result<Message, SerenityError>
     where
-        F: Send + FnOnce(&mut CreateEmbed) -> &mut CreateEmbed;
+        F: Send + FnOnce(CreateEmbed) -> CreateEmbed;
 }
 #[async_trait]
 impl EmbedReply for Message {
     async fn ereply<F>(&self, ctx: &Context, fun: F) -> Result<Message, SerenityError>
     where
-        F: Send + FnOnce(&mut CreateEmbed) -> &mut CreateEmbed,
+        F: Send + FnOnce(CreateEmbed) -> CreateEmbed,
     {
+        let embed = fun(embed_template(self));
         self.channel_id
-            .send_message(ctx, |m| {
-                m.embed(|e| {
-                    embed_template(self, e);
-                    fun(e)
-                })
-            })
+            .send_message(ctx, CreateMessage::new().embed(embed))
             .await
     }
 }
@@ -149,30 +154,74 @@ impl EmbedReply for Message {
 pub trait EmbedEdit {
     async fn eedit<F>(&mut self, ctx: &Context, f: F) -> Result<(), SerenityError>
     where
-        F: Send + FnOnce(&mut CreateEmbed) -> &mut CreateEmbed;
+        F: Send + FnOnce(CreateEmbed) -> CreateEmbed;
 }
 #[async_trait]
 impl EmbedEdit for Message {
     async fn eedit<F>(&mut self, ctx: &Context, fun: F) -> Result<(), SerenityError>
     where
-        F: Send + FnOnce(&mut CreateEmbed) -> &mut CreateEmbed,
+        F: Send + FnOnce(CreateEmbed) -> CreateEmbed,
     {
-        let footer = self.embeds.iter().filter_map(|e| e.footer.clone()).next();
-        let colour = self.embeds.iter().next().map(|e| e.colour);
-
-        self.edit(ctx, |m| {
-            m.embed(|e| {
-                colour.map(|c| e.colour(c));
-                footer.map(|footer| {
-                    e.footer(|f| {
-                        footer.icon_url.map(|i| f.icon_url(i));
-                        f.text(footer.text)
-                    })
-                });
-                fun(e)
-            })
-        })
-        .await
+        let embed = fun(EmbedTemplate::of(self).apply(CreateEmbed::new()));
+        self.edit(ctx, EditMessage::new().embed(embed)).await
+    }
+}
+
+#[async_trait]
+pub trait EmbedAttach {
+    async fn eattach<F>(
+        &mut self,
+        ctx: &Context,
+        file: CreateAttachment,
+        f: F,
+    ) -> Result<(), SerenityError>
+    where
+        F: Send + FnOnce(CreateEmbed) -> CreateEmbed;
+}
+#[async_trait]
+impl EmbedAttach for Message {
+    /// Edits the embed and replaces the attachments of the message with the file.
+    async fn eattach<F>(
+        &mut self,
+        ctx: &Context,
+        file: CreateAttachment,
+        fun: F,
+    ) -> Result<(), SerenityError>
+    where
+        F: Send + FnOnce(CreateEmbed) -> CreateEmbed,
+    {
+        let embed = fun(EmbedTemplate::of(self).apply(CreateEmbed::new()));
+        self.edit(ctx, EditMessage::new().embed(embed).new_attachment(file))
+            .await
+    }
+}
+
+/// footer and colour of an embed, to keep them when the embed is changed
+struct EmbedTemplate {
+    footer: Option<EmbedFooter>,
+    colour: Option<Colour>,
+}
+
+impl EmbedTemplate {
+    fn of(msg: &Message) -> Self {
+        Self {
+            footer: msg.embeds.iter().filter_map(|e| e.footer.clone()).next(),
+            colour: msg.embeds.first().and_then(|e| e.colour),
+        }
+    }
+
+    fn apply(self, mut e: CreateEmbed) -> CreateEmbed {
+        if let Some(colour) = self.colour {
+            e = e.colour(colour);
+        }
+        if let Some(footer) = self.footer {
+            let mut f = CreateEmbedFooter::new(footer.text);
+            if let Some(icon) = footer.icon_url {
+                f = f.icon_url(icon);
+            }
+            e = e.footer(f);
+        }
+        e
     }
 }
 
@@ -183,12 +232,8 @@ pub trait EmbedErrorReply {
 #[async_trait]
 impl EmbedErrorReply for Message {
     async fn err_reply(&self, ctx: &Context, err: &str) -> CommandResult {
-        self.ereply(ctx, |e| {
-            e.color(Color::RED);
-            e.title("Error");
-            e.description(err)
-        })
-        .await?;
+        self.ereply(ctx, |e| e.color(Color::RED).title("Error").description(err))
+            .await?;
         Err(err)?
     }
 }
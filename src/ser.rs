@@ -1,14 +1,12 @@
 pub use ::serenity::builder::*;
 pub use ::serenity::framework::standard::*;
 pub use ::serenity::model::prelude::*;
-pub use ::serenity::model::user::*;
 pub use ::serenity::prelude::*;
-pub use ::serenity::utils::*;
 pub use ::serenity::{
     async_trait,
-    client::bridge::gateway::ShardManager,
-    framework::{standard::macros::*, standard::*, StandardFramework},
+    client::FullEvent,
+    framework::{standard::macros::*, standard::Command, Framework, StandardFramework},
+    gateway::{ActivityData, ShardManager},
     http::Http,
-    model::{channel::*, event::ResumedEvent, gateway::*, id::*},
-    prelude::*,
+    model::{application::Command as SlashCommand, event::ResumedEvent},
 };
@@ -34,7 +34,6 @@ pub async fn confirm_dialog(
             .timeout(Duration::from_secs_f64(TIMEOUT - elapsed))
             .await;
         let reaction = tryc!(reaction);
-        let reaction = reaction.as_inner_ref();
         if reaction.user_id == Some(person.id) {
             break true;
         }
@@ -0,0 +1,156 @@
+//! A bounded pool for jobs that block a thread, like running brainfuck programs.
+//!
+//! Only a few jobs run at the same time, the others wait in a queue in the order they came in.
+//! While a job waits, its author is told so and can cancel it. Jobs that run for a while can be
+//! cancelled with a reaction on the message that started them.
+
+use crate::prelude::*;
+use crate::ser::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// How many jobs run at the same time
+const MAX_JOBS: usize = 4;
+
+/// How many jobs can wait for a free worker
+const MAX_QUEUED: usize = 32;
+
+/// Reaction that cancels a queued or running job
+const CANCEL: &str = "❌";
+
+/// How long a job runs before its author can cancel it, so short jobs don't get a reaction
+const CANCEL_DELAY: Duration = Duration::from_secs(2);
+
+lazy_static! {
+    static ref WORKERS: Semaphore = Semaphore::new(MAX_JOBS);
+}
+
+/// number of jobs that wait for a worker
+static QUEUED: AtomicUsize = AtomicUsize::new(0);
+
+/// A place in the queue, which is given up when it's dropped
+struct Queued;
+
+impl Queued {
+    /// Enters the queue, returns how many jobs are ahead or `None` if the queue is full
+    fn enter() -> Option<(Self, usize)> {
+        let ahead = QUEUED.fetch_add(1, Ordering::SeqCst);
+        let queued = Self;
+        if ahead >= MAX_QUEUED {
+            return None;
+        }
+        Some((queued, ahead))
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        QUEUED.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Sets the flag of a job when it's dropped, so the job stops once nobody waits for it
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Runs a job on a worker thread once one is free. The job gets a flag that is set when it
+/// should stop early, like when the author cancels it while it runs.
+/// If all workers are busy, a reply says that the job is queued.
+/// `None` if the author cancelled the queued job.
+pub async fn run_job<T, F>(ctx: &Context, msg: &Message, job: F) -> CommandResult<Option<T>>
+where
+    T: Send + 'static,
+    F: FnOnce(&AtomicBool) -> T + Send + 'static,
+{
+    let permit = match WORKERS.try_acquire() {
+        Ok(permit) => permit,
+        Err(_) => match wait_in_queue(ctx, msg).await? {
+            Some(permit) => permit,
+            None => return Ok(None),
+        },
+    };
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let _guard = CancelOnDrop(cancel.clone());
+    let flag = cancel.clone();
+    let mut running = tokio::task::spawn_blocking(move || {
+        // the worker stays busy until the job really stopped
+        let _permit = permit;
+        job(&flag)
+    });
+
+    let start = Instant::now();
+    let result = tokio::select! {
+        result = &mut running => result?,
+        _ = cancel_when_asked(ctx, msg, &cancel) => running.await?,
+    };
+    if start.elapsed() >= CANCEL_DELAY {
+        msg.delete_reaction(ctx, None, ReactionType::Unicode(CANCEL.into()))
+            .await
+            .ok();
+    }
+    Ok(Some(result))
+}
+
+/// Sets the flag once the author reacts to cancel a job that runs for a while
+async fn cancel_when_asked(ctx: &Context, msg: &Message, cancel: &AtomicBool) {
+    tokio::time::sleep(CANCEL_DELAY).await;
+    msg.react(ctx, ReactionType::Unicode(CANCEL.into()))
+        .await
+        .ok();
+
+    let author = msg.author.id;
+    let reaction = msg.await_reaction(ctx).filter(move |r| {
+        r.user_id == Some(author) && r.emoji == ReactionType::Unicode(CANCEL.into())
+    });
+    if reaction.await.is_some() {
+        cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Waits for a free worker, `None` if the author cancelled the job
+async fn wait_in_queue(
+    ctx: &Context,
+    msg: &Message,
+) -> CommandResult<Option<tokio::sync::SemaphorePermit<'static>>> {
+    let (_queued, ahead) = match Queued::enter() {
+        Some(queued) => queued,
+        None => {
+            msg.err_reply(ctx, "The bot is too busy right now, try again later.")
+                .await?;
+            return Ok(None);
+        }
+    };
+
+    let notice = msg
+        .ereply(ctx, |e| {
+            e.title("Queued").description(format!(
+                "All workers are busy, {} jobs are ahead of yours. React with {} to cancel it.",
+                ahead, CANCEL
+            ))
+        })
+        .await?;
+    notice
+        .react(ctx, ReactionType::Unicode(CANCEL.into()))
+        .await
+        .ok();
+
+    let author = msg.author.id;
+    let cancelled = notice.await_reaction(ctx).filter(move |r| {
+        r.user_id == Some(author) && r.emoji == ReactionType::Unicode(CANCEL.into())
+    });
+
+    let permit = tokio::select! {
+        permit = WORKERS.acquire() => Some(permit?),
+        Some(_) = cancelled => None,
+    };
+    notice.delete(ctx).await.ok();
+    Ok(permit)
+}